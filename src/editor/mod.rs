@@ -0,0 +1,753 @@
+use super::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::PrimaryWindow;
+
+const MAX_MAP_SIZE: i32 = 12;
+const PANEL_WIDTH: f32 = 260.0;
+
+#[derive(Clone, PartialEq)]
+pub enum Brush {
+    Erase,
+    Wall,
+    Fire(Intensity),
+    Terrain(Tile),
+    Water,
+    Energy,
+    Card,
+    Start,
+}
+
+impl Brush {
+    fn label(&self) -> &'static str {
+        match self {
+            Brush::Erase => "Erase",
+            Brush::Wall => "Wall",
+            Brush::Fire(Intensity::Low) => "Fire 1",
+            Brush::Fire(Intensity::Medium) => "Fire 2",
+            Brush::Fire(Intensity::High) => "Fire 3",
+            Brush::Terrain(Tile::Puddle) => "Puddle",
+            Brush::Terrain(Tile::Fuel) => "Fuel",
+            Brush::Terrain(Tile::Door) => "Door",
+            Brush::Terrain(_) => "Scorched",
+            Brush::Water => "Water",
+            Brush::Energy => "Energy",
+            Brush::Card => "Card",
+            Brush::Start => "Start",
+        }
+    }
+}
+
+/// The puzzle being edited. It survives play-testing so the designer comes
+/// back to where they left off.
+#[derive(Resource)]
+pub struct EditorState {
+    pub level: Level,
+    pub brush: Brush,
+    pub card: ContentID,
+    pub status: String,
+    pub next_puzzle: usize,
+    /// The file an export was held back for because the puzzle already
+    /// exists. Exporting again straight away goes ahead.
+    pub overwrite: Option<String>,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        let mut level = Level::new();
+        level.name = "New Puzzle".to_string();
+        level.map_size = (5, 5);
+        level.deck_list = vec![1, 1, 1];
+        Self {
+            level,
+            brush: Brush::Fire(Intensity::Low),
+            card: ContentID(1),
+            status: "Left click paints, right click erases".to_string(),
+            next_puzzle: 0,
+            overwrite: None,
+        }
+    }
+}
+
+impl EditorState {
+    fn clear_tile(&mut self, x: i32, y: i32) {
+        let level = &mut self.level;
+        level.walls.retain(|wall| *wall != (x, y));
+        level.flames.retain(|(fx, fy, _)| (*fx, *fy) != (x, y));
+        level.terrain.retain(|(tx, ty, _)| (*tx, *ty) != (x, y));
+        level.items.retain(|(ix, iy, _)| (*ix, *iy) != (x, y));
+    }
+
+    fn paint(&mut self, x: i32, y: i32, brush: &Brush) {
+        let (columns, rows) = self.level.map_size;
+        if x < 1 || y < 1 || x > columns || y > rows {
+            return;
+        }
+        let is_start = self.level.start.x == x && self.level.start.y == y;
+        if is_start && *brush != Brush::Start {
+            if *brush != Brush::Erase {
+                self.status = "The start tile must stay clear".to_string();
+            }
+            return;
+        }
+        match brush {
+            Brush::Start if is_start => {
+                self.level.start = self.level.start.rotated(&Rotation::Right);
+                return;
+            }
+            Brush::Start => {
+                self.clear_tile(x, y);
+                self.level.start.x = x;
+                self.level.start.y = y;
+                return;
+            }
+            _ => self.clear_tile(x, y),
+        }
+        match brush {
+            Brush::Wall => self.level.walls.push((x, y)),
+            Brush::Fire(intensity) => self.level.flames.push((x, y, intensity.clone())),
+            Brush::Terrain(tile) => self.level.terrain.push((x, y, tile.clone())),
+            Brush::Water => self.level.items.push((x, y, Item::Water)),
+            Brush::Energy => self.level.items.push((x, y, Item::Energy)),
+            Brush::Card => self.level.items.push((x, y, Item::Card(self.card.clone()))),
+            Brush::Erase | Brush::Start => {}
+        }
+    }
+
+    fn resize(&mut self, columns: i32, rows: i32) {
+        let columns = columns.clamp(1, MAX_MAP_SIZE);
+        let rows = rows.clamp(1, MAX_MAP_SIZE);
+        let level = &mut self.level;
+        level.map_size = (columns, rows);
+        let inside = |x: i32, y: i32| x <= columns && y <= rows;
+        level.walls.retain(|(x, y)| inside(*x, *y));
+        level.flames.retain(|(x, y, _)| inside(*x, *y));
+        level.terrain.retain(|(x, y, _)| inside(*x, *y));
+        level.items.retain(|(x, y, _)| inside(*x, *y));
+        level.start.x = level.start.x.min(columns);
+        level.start.y = level.start.y.min(rows);
+        let (x, y) = (level.start.x, level.start.y);
+        self.clear_tile(x, y);
+    }
+
+    /// Why the puzzle can't be played yet, if anything.
+    fn problem(&self) -> Option<&'static str> {
+        if self.level.flames.is_empty() {
+            Some("Place at least one fire")
+        } else if self.level.deck_list.is_empty() {
+            Some("The deck needs at least one card")
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum EditorAction {
+    Brush(Brush),
+    Columns(i32),
+    Rows(i32),
+    PreviousCard,
+    NextCard,
+    AddToDeck,
+    RemoveFromDeck,
+    PlayTest,
+    Export,
+    Open,
+    Clear,
+    Back,
+}
+
+#[derive(Component)]
+pub struct EditorButton(pub EditorAction);
+
+#[derive(Component)]
+pub enum EditorText {
+    Name,
+    Size,
+    Card,
+    Deck,
+    Status,
+}
+
+#[derive(Component)]
+pub struct EditorUI;
+
+#[derive(Component)]
+pub struct EditorSprite;
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, action: EditorAction, width: Val) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    width,
+                    min_height: Val::Px(28.0),
+                    margin: UiRect::all(Val::Px(2.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: Color::NONE.into(),
+                background_color: Color::TEAL.into(),
+                ..default()
+            },
+            RelativeCursorPosition::default(),
+            EditorButton(action),
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn spawn_row(parent: &mut ChildBuilder, children: impl FnOnce(&mut ChildBuilder)) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                align_items: AlignItems::Center,
+                width: Val::Percent(100.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(children);
+}
+
+fn spawn_text(parent: &mut ChildBuilder, text: EditorText, font_size: f32) {
+    parent.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),
+        text,
+    ));
+}
+
+fn spawn_editor(mut commands: Commands, mut state: ResMut<EditorState>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    width: Val::Px(PANEL_WIDTH),
+                    height: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+                ..default()
+            },
+            Interaction::default(),
+            EditorUI,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                "Level Editor",
+                TextStyle {
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            panel.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                Interaction::default(),
+                Tooltip {
+                    text: "Type to rename the puzzle".to_string(),
+                    threshold: 0.5,
+                },
+                EditorText::Name,
+            ));
+            spawn_text(panel, EditorText::Size, 18.0);
+            spawn_row(panel, |row| {
+                spawn_button(row, "Cols -", EditorAction::Columns(-1), Val::Px(56.0));
+                spawn_button(row, "Cols +", EditorAction::Columns(1), Val::Px(56.0));
+                spawn_button(row, "Rows -", EditorAction::Rows(-1), Val::Px(56.0));
+                spawn_button(row, "Rows +", EditorAction::Rows(1), Val::Px(56.0));
+            });
+            spawn_row(panel, |row| {
+                for brush in [
+                    Brush::Erase,
+                    Brush::Wall,
+                    Brush::Start,
+                    Brush::Fire(Intensity::Low),
+                    Brush::Fire(Intensity::Medium),
+                    Brush::Fire(Intensity::High),
+                    Brush::Terrain(Tile::Puddle),
+                    Brush::Terrain(Tile::Fuel),
+                    Brush::Terrain(Tile::Door),
+                    Brush::Terrain(Tile::Scorched),
+                    Brush::Water,
+                    Brush::Energy,
+                    Brush::Card,
+                ] {
+                    spawn_button(
+                        row,
+                        brush.label(),
+                        EditorAction::Brush(brush),
+                        Val::Px(76.0),
+                    );
+                }
+            });
+            spawn_row(panel, |row| {
+                spawn_button(row, "<", EditorAction::PreviousCard, Val::Px(28.0));
+                spawn_text(row, EditorText::Card, 18.0);
+                spawn_button(row, ">", EditorAction::NextCard, Val::Px(28.0));
+            });
+            spawn_row(panel, |row| {
+                spawn_button(row, "Add to deck", EditorAction::AddToDeck, Val::Px(116.0));
+                spawn_button(
+                    row,
+                    "Remove last",
+                    EditorAction::RemoveFromDeck,
+                    Val::Px(116.0),
+                );
+            });
+            spawn_text(panel, EditorText::Deck, 16.0);
+            spawn_row(panel, |row| {
+                spawn_button(row, "Play Test", EditorAction::PlayTest, Val::Px(116.0));
+                spawn_button(row, "Export", EditorAction::Export, Val::Px(116.0));
+                spawn_button(row, "Open Puzzle", EditorAction::Open, Val::Px(116.0));
+                spawn_button(row, "Clear", EditorAction::Clear, Val::Px(116.0));
+                spawn_button(row, "Main Menu", EditorAction::Back, Val::Px(116.0));
+            });
+            spawn_text(panel, EditorText::Status, 16.0);
+        });
+    // Force the map and texts to be drawn
+    state.set_changed();
+}
+
+fn despawn_editor(
+    mut commands: Commands,
+    entities: Query<Entity, Or<(With<EditorUI>, With<EditorSprite>)>>,
+) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn handle_editor_buttons(
+    mut commands: Commands,
+    interactions: Query<(&Interaction, &EditorButton), Changed<Interaction>>,
+    mut state: ResMut<EditorState>,
+    card_infos: Res<CardInfoMap>,
+    puzzles: Res<Puzzles>,
+    levels: Res<Assets<Level>>,
+    mut game_mode: ResMut<GameMode>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let mut card_ids: Vec<ContentID> = card_infos.0.keys().cloned().collect();
+    card_ids.sort_by_key(|id| id.0);
+    for (interaction, button) in interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let overwrite = state.overwrite.take();
+        match &button.0 {
+            EditorAction::Brush(brush) => state.brush = brush.clone(),
+            EditorAction::Columns(change) => {
+                let (columns, rows) = state.level.map_size;
+                state.resize(columns + change, rows);
+            }
+            EditorAction::Rows(change) => {
+                let (columns, rows) = state.level.map_size;
+                state.resize(columns, rows + change);
+            }
+            EditorAction::PreviousCard | EditorAction::NextCard if !card_ids.is_empty() => {
+                let index = card_ids
+                    .iter()
+                    .position(|id| *id == state.card)
+                    .unwrap_or(0);
+                let index = if button.0 == EditorAction::NextCard {
+                    (index + 1) % card_ids.len()
+                } else {
+                    (index + card_ids.len() - 1) % card_ids.len()
+                };
+                state.card = card_ids[index].clone();
+            }
+            EditorAction::PreviousCard | EditorAction::NextCard => {}
+            EditorAction::AddToDeck => {
+                let card = state.card.0;
+                state.level.deck_list.push(card);
+            }
+            EditorAction::RemoveFromDeck => {
+                state.level.deck_list.pop();
+            }
+            EditorAction::PlayTest => match state.problem() {
+                Some(problem) => state.status = problem.to_string(),
+                None => {
+                    commands.insert_resource(PlaytestLevel(state.level.clone()));
+                    *game_mode = GameMode::Puzzle;
+                    app_state.set(AppState::Game);
+                    game_state.set(GameState::Loading);
+                }
+            },
+            EditorAction::Export => {
+                if let Some(problem) = state.problem() {
+                    state.status = problem.to_string();
+                    continue;
+                }
+                let file_name = puzzle_file_name(&state.level);
+                let stem = file_stem(&state.level.name);
+                let name_taken = (0..puzzles.len()).any(|index| {
+                    puzzles
+                        .get(index, &levels)
+                        .is_some_and(|level| file_stem(&level.name) == stem)
+                });
+                let taken = name_taken || is_exported(&file_name);
+                if taken && overwrite.as_ref() != Some(&file_name) {
+                    state.status = format!(
+                        "A puzzle named {} already exists, export again to save it anyway",
+                        state.level.name
+                    );
+                    state.overwrite = Some(file_name);
+                } else {
+                    state.status = export_puzzle(&state.level);
+                }
+            }
+            EditorAction::Open => {
+                let index = state.next_puzzle;
+                match puzzles.get(index, &levels) {
+                    Some(level) => {
+                        state.level = level.clone();
+                        state.status = format!("Opened {}", level.name);
+                        state.next_puzzle = (index + 1) % puzzles.len();
+                    }
+                    None => state.status = "No puzzles have been loaded".to_string(),
+                }
+            }
+            EditorAction::Clear => *state = EditorState::default(),
+            EditorAction::Back => app_state.set(AppState::MainMenu),
+        }
+    }
+}
+
+/// Lower case letters, digits and underscores only, so it is safe as a file name.
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() {
+        "custom".to_string()
+    } else {
+        stem
+    }
+}
+
+fn puzzle_file_name(level: &Level) -> String {
+    format!("{}.puzzle", file_stem(&level.name))
+}
+
+/// Whether a puzzle file of this name has already been written.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_exported(file_name: &str) -> bool {
+    std::path::Path::new("assets")
+        .join(PUZZLE_FOLDER)
+        .join(file_name)
+        .exists()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn is_exported(file_name: &str) -> bool {
+    read_save(file_name).is_some()
+}
+
+/// Writes the puzzle where the game will find it and returns a status line.
+#[cfg(not(target_arch = "wasm32"))]
+fn export_puzzle(level: &Level) -> String {
+    let path = std::path::Path::new("assets")
+        .join(PUZZLE_FOLDER)
+        .join(puzzle_file_name(level));
+    match std::fs::write(&path, puzzle_text(level)) {
+        Ok(_) => format!("Exported to {}", path.display()),
+        Err(error) => format!("Export failed: {}", error),
+    }
+}
+
+/// The web build can't write asset files, so the puzzle is kept in browser
+/// storage and logged to the console for copying.
+#[cfg(target_arch = "wasm32")]
+fn export_puzzle(level: &Level) -> String {
+    let file_name = puzzle_file_name(level);
+    let text = puzzle_text(level);
+    info!("{}:\n{}", file_name, text);
+    write_save(&file_name, &text);
+    format!("Saved {} to browser storage and the console", file_name)
+}
+
+fn handle_name_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<EditorState>,
+) {
+    for event in characters.read() {
+        if !event.char.is_control() && state.level.name.len() < 32 {
+            state.level.name.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        state.level.name.pop();
+    }
+}
+
+fn paint_tiles(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    panels: Query<&Interaction, With<EditorUI>>,
+    mut state: ResMut<EditorState>,
+    mut last_painted: Local<Option<(i32, i32)>>,
+) {
+    let brush = if mouse_input.pressed(MouseButton::Left) {
+        state.brush.clone()
+    } else if mouse_input.pressed(MouseButton::Right) {
+        Brush::Erase
+    } else {
+        *last_painted = None;
+        return;
+    };
+    if panels
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let Some(cursor) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Some(world_position) = camera.viewport_to_world_2d(camera_transform, cursor) else {
+        return;
+    };
+    let tile = (
+        (world_position.x / 64.0).round() as i32,
+        (world_position.y / 64.0).round() as i32,
+    );
+    // Holding the button paints each tile once, so the start only turns per click
+    if *last_painted == Some(tile) {
+        return;
+    }
+    *last_painted = Some(tile);
+    state.paint(tile.0, tile.1, &brush);
+}
+
+fn draw_level(
+    mut commands: Commands,
+    state: Res<EditorState>,
+    sprites: Query<Entity, With<EditorSprite>>,
+    tile_sprites: Res<TileSpriteSheet>,
+    item_sprites: Res<ItemSpriteSheet>,
+    player_sprites: Res<PlayerSpriteSheet>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let level = &state.level;
+    let (columns, rows) = level.map_size;
+    for y in 0..=rows + 1 {
+        for x in 0..=columns + 1 {
+            let is_border = x == 0 || y == 0 || x == columns + 1 || y == rows + 1;
+            let flame = level
+                .flames
+                .iter()
+                .find(|(fx, fy, _)| (*fx, *fy) == (x, y))
+                .map(|(_, _, intensity)| Tile::Fire(intensity.clone()));
+            let terrain = level
+                .terrain
+                .iter()
+                .find(|(tx, ty, _)| (*tx, *ty) == (x, y))
+                .map(|(_, _, tile)| tile.clone());
+            let tile = if is_border || level.walls.contains(&(x, y)) {
+                Tile::Wall
+            } else {
+                flame.or(terrain).unwrap_or(Tile::Empty)
+            };
+            let index = tile.sprite_index();
+            commands.spawn((
+                SpriteSheetBundle {
+                    sprite: TextureAtlasSprite::new(index),
+                    texture_atlas: tile_sprites.0.clone(),
+                    transform: Transform::from_xyz(x as f32 * 64.0, y as f32 * 64.0, 0.0),
+                    ..default()
+                },
+                EditorSprite,
+            ));
+        }
+    }
+    for (x, y, item) in level.items.iter() {
+        commands.spawn((
+            SpriteSheetBundle {
+                sprite: TextureAtlasSprite::new(match item {
+                    Item::Water => 0,
+                    Item::Energy => 1,
+                    Item::Card(_) => 2,
+                }),
+                texture_atlas: item_sprites.0.clone(),
+                transform: Transform::from_xyz(*x as f32 * 64.0, *y as f32 * 64.0, 1.0),
+                ..default()
+            },
+            EditorSprite,
+        ));
+    }
+    commands.spawn((
+        SpriteSheetBundle {
+            sprite: TextureAtlasSprite::new(0),
+            texture_atlas: player_sprites.0.clone(),
+            transform: Transform::from_xyz(
+                level.start.x as f32 * 64.0,
+                level.start.y as f32 * 64.0,
+                2.0,
+            )
+            .with_rotation(level.start.d.get_quat()),
+            ..default()
+        },
+        EditorSprite,
+    ));
+    for (mut transform, mut projection) in cameras.iter_mut() {
+        projection.scale = match columns.max(rows) {
+            0..=4 => 1.0,
+            5..=8 => 1.2,
+            _ => 1.5,
+        };
+        // Keep the map clear of the panel on the left
+        transform.translation.x =
+            (columns + 1) as f32 * 64.0 / 2.0 - PANEL_WIDTH / 2.0 * projection.scale;
+        transform.translation.y = (rows + 1) as f32 * 64.0 / 2.0;
+    }
+}
+
+fn update_editor_texts(
+    state: Res<EditorState>,
+    card_infos: Res<CardInfoMap>,
+    mut texts: Query<(&EditorText, &mut Text)>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let card_name = |id: &ContentID| {
+        card_infos
+            .0
+            .get(id)
+            .map(|card_info| card_info.name.clone())
+            .unwrap_or_else(|| format!("#{}", id.0))
+    };
+    for (kind, mut text) in texts.iter_mut() {
+        text.sections[0].value = match kind {
+            EditorText::Name => format!("Name: {}", state.level.name),
+            EditorText::Size => format!(
+                "Size: {} x {}",
+                state.level.map_size.0, state.level.map_size.1
+            ),
+            EditorText::Card => card_name(&state.card),
+            EditorText::Deck => {
+                let names: Vec<String> = state
+                    .level
+                    .deck_list
+                    .iter()
+                    .map(|id| card_name(&ContentID(*id)))
+                    .collect();
+                format!("Deck: {}", names.join(", "))
+            }
+            EditorText::Status => state.status.clone(),
+        };
+    }
+}
+
+fn update_brush_buttons(
+    state: Res<EditorState>,
+    mut buttons: Query<(&EditorButton, &mut BorderColor)>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for (button, mut border) in buttons.iter_mut() {
+        border.0 = match &button.0 {
+            EditorAction::Brush(brush) if *brush == state.brush => Color::YELLOW,
+            _ => Color::NONE,
+        };
+    }
+}
+
+/// Sends the designer back to the editor once a play-test ends, however it ends.
+fn return_from_playtest(
+    mut commands: Commands,
+    app_state: Res<State<AppState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+) {
+    if matches!(app_state.get(), AppState::Game | AppState::Editor) {
+        return;
+    }
+    commands.remove_resource::<PlaytestLevel>();
+    next_app_state.set(AppState::Editor);
+    next_game_state.set(GameState::None);
+    next_turn_state.set(TurnState::None);
+}
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorState>()
+            .add_systems(OnEnter(AppState::Editor), spawn_editor)
+            .add_systems(OnExit(AppState::Editor), despawn_editor)
+            .add_systems(
+                Update,
+                (
+                    handle_editor_buttons,
+                    handle_name_input,
+                    paint_tiles,
+                    draw_level,
+                    update_editor_texts,
+                    update_brush_buttons,
+                )
+                    .run_if(in_state(AppState::Editor)),
+            )
+            .add_systems(
+                Update,
+                return_from_playtest.run_if(resource_exists::<PlaytestLevel>()),
+            );
+    }
+}
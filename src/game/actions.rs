@@ -6,12 +6,6 @@ pub struct Draw {
     pub hand: Entity,
 }
 
-#[derive(Clone, Debug)]
-pub struct Recycle {
-    pub card: Entity,
-    pub hand: Entity,
-}
-
 #[derive(Clone, Debug)]
 pub struct Discard {
     pub card: Entity,
@@ -26,39 +20,78 @@ pub struct Play {
     pub hand: Entity,
 }
 
-#[allow(dead_code)]
 #[derive(Clone, Component, Debug)]
 pub enum CardActionType {
     Draw(Draw),
-    Recycle(Recycle),
     Discard(Discard),
     Play(Play),
 }
 
+/// How many cards the player still has to pick from their hand to discard.
+/// Clicking a card discards it instead of playing it while this exists.
+#[derive(Resource)]
+pub struct PendingDiscard(pub u32);
+
+pub fn clear_pending_discard(mut commands: Commands) {
+    commands.remove_resource::<PendingDiscard>();
+}
+
+/// Discards the clicked card while the player is choosing discards.
+pub fn discard_clicked_card(
+    mut commands: Commands,
+    mut events: EventReader<CardClicked>,
+    player: Query<(Entity, &Hand), With<Player>>,
+) {
+    let (player_id, hand) = player.get_single().expect("Should be exactly 1 player");
+    for event in events.read() {
+        let Some(card) = event.card_instance.0 else {
+            continue;
+        };
+        if hand.0.contains(&Some(card)) {
+            commands.spawn(CardActionType::Discard(Discard {
+                card,
+                deck: player_id,
+                hand: player_id,
+            }));
+        }
+    }
+}
+
+/// Lets the turn carry on if there is nothing left in the hand to discard.
+pub fn skip_impossible_discard(mut commands: Commands, hand: Query<&Hand, With<Player>>) {
+    let hand = hand.get_single().expect("Should be exactly 1 hand");
+    if hand.empty_slots() == HAND_SIZE {
+        commands.remove_resource::<PendingDiscard>();
+    }
+}
+
 pub fn apply_card_actions(
     mut commands: Commands,
     actions: Query<(Entity, &CardActionType)>,
     mut decks: Query<&mut Deck>,
     mut hands: Query<&mut Hand>,
+    mut rng: ResMut<GameRng>,
+    mut pending_discard: Option<ResMut<PendingDiscard>>,
 ) {
     for (entity, action) in actions.iter() {
         match action {
             CardActionType::Draw(action) => {
                 let mut deck = decks.get_mut(action.deck).expect("Failed to get the deck");
                 let mut hand = hands.get_mut(action.hand).expect("Failed to get the hand");
-                if let Some(card) = deck.draw() {
-                    hand.add(card);
-                }
-            }
-            CardActionType::Recycle(action) => {
-                let mut hand = hands.get_single_mut().expect("Failed to get the hand");
-                hand.remove(action.card);
-                let mut deck = decks.get_single_mut().expect("Failed to get the deck");
-                deck.recycle(action.card);
+                hand.draw(&mut deck, &mut *rng);
             }
             CardActionType::Discard(action) => {
                 let mut hand = hands.get_mut(action.hand).expect("Failed to get the hand");
                 let mut deck = decks.get_mut(action.deck).expect("Failed to get the deck");
+                // A card leaving the hand is one the player chose to discard
+                if hand.0.contains(&Some(action.card)) {
+                    if let Some(pending) = pending_discard.as_mut() {
+                        pending.0 = pending.0.saturating_sub(1);
+                        if pending.0 == 0 {
+                            commands.remove_resource::<PendingDiscard>();
+                        }
+                    }
+                }
                 hand.remove(action.card);
                 deck.discard(action.card);
             }
@@ -95,146 +128,167 @@ pub fn apply_change<T: Component + Clone>(
 #[derive(Component)]
 pub struct WasPlayed(pub Entity);
 
+/// Applies a played card's effects on the hand and deck in the order the card
+/// lists them, so a recycle followed by a draw draws into the emptied hand.
+/// The card itself has already been recycled by its play action.
+/// Returns how many cards the player has to discard.
+pub fn apply_hand_effects<R: Rng + ?Sized>(
+    card: Entity,
+    effects: &[CardEffect],
+    hand: &mut Hand,
+    deck: &mut Deck,
+    rng: &mut R,
+) -> u32 {
+    if effects.contains(&CardEffect::Exhaust) {
+        deck.discard(card);
+    }
+    let mut discards = 0;
+    for effect in effects.iter() {
+        match effect {
+            CardEffect::Draw(count) => {
+                for _ in 0..*count {
+                    hand.draw(deck, rng);
+                }
+            }
+            CardEffect::Discard(count) => discards += count,
+            CardEffect::RecycleHand => {
+                for card in hand.0.iter_mut().filter_map(Option::take) {
+                    deck.recycle(card);
+                }
+            }
+            _ => {}
+        }
+    }
+    discards
+}
+
 pub fn apply_card(
     mut commands: Commands,
     mut turn_state: ResMut<NextState<TurnState>>,
     card_infos: Res<CardInfoMap>,
-    player: Query<(Entity, &Energy, &Water), With<Player>>,
+    mut rng: ResMut<GameRng>,
+    mut player: Query<
+        (Entity, &GamePosition, &Energy, &Water, &mut Hand, &mut Deck),
+        With<Player>,
+    >,
     played_cards: Query<(Entity, &WasPlayed)>,
-    card_instances: Query<&ContentID>,
-    game_positions: Query<&GamePosition>,
+    card_instances: Query<(&ContentID, Option<&Upgrade>)>,
     grid: Query<&Grid>,
     tiles: Query<&Tile>,
 ) {
-    let (player_id, energy, water) = player
-        .get_single()
+    let (player_id, base_pos, energy, water, mut hand, mut deck) = player
+        .get_single_mut()
         .expect("There should only be one player");
     for (was_played_id, played_card) in played_cards.iter() {
         let card_instance_id = played_card.0;
-        let card_info_id = card_instances
+        let (card_info_id, upgrade) = card_instances
             .get(card_instance_id)
             .expect("Failed to get card instance");
         let card_info = card_infos
-            .0
-            .get(&*card_info_id)
+            .effective(card_info_id, upgrade)
             .expect("Failed to get card info");
+        let grid = grid.get_single().expect("Failed to get grid");
+        let board = grid.board(|id| tiles.get(id).ok().cloned());
+        let Some(play) =
+            CardPlay::resolve(card_info_id, &card_info, base_pos, energy, water, &board)
+        else {
+            warn!("{} was played but can't be", card_info.name);
+            commands.entity(was_played_id).despawn_recursive();
+            continue;
+        };
         commands.spawn(Change {
             entity: player_id,
-            updated_value: Energy {
-                current: energy.current - card_info.resource_cost.energy,
-                ..energy.clone()
-            },
+            updated_value: play.energy.clone(),
         });
         commands.spawn(Change {
             entity: player_id,
-            updated_value: Water {
-                current: water.current - card_info.resource_cost.water,
-                ..water.clone()
-            },
+            updated_value: play.water.clone(),
         });
-        match &card_info.position_change {
-            MovementInfo {
-                position: TileTarget::FacingDist(dist),
-                rotation: rot,
-            } => {
-                let base_pos = game_positions
-                    .get(player_id)
-                    .expect("Failed to get player position");
-                let new_pos = base_pos.rotated(&rot).offset((*dist, 0));
-                commands.spawn(Animation {
-                    animation_type: AnimationType::Move(
-                        player_id,
-                        Vec2 {
-                            x: new_pos.x as f32,
-                            y: new_pos.y as f32,
-                        },
-                    ),
-                    duration: 0.35,
-                    parent: None,
-                });
-                if rot != &Rotation::None {
-                    commands.spawn(Animation {
-                        animation_type: AnimationType::Rotate(player_id, new_pos.d.get_quat()),
-                        duration: 0.35,
-                        parent: None,
-                    });
-                }
-                commands.spawn(Change {
-                    entity: player_id,
-                    updated_value: new_pos,
-                });
-            }
-            MovementInfo {
-                position: TileTarget::FacingOffsets(offsets),
-                rotation: rot,
-            } => {
-                let base_pos = game_positions
-                    .get(player_id)
-                    .expect("Failed to get player position");
-                let new_pos =
-                    &TileTarget::FacingOffsets(offsets.to_vec()).get_positions(base_pos)[0];
-                commands.spawn(Animation {
-                    animation_type: AnimationType::Move(
-                        player_id,
-                        Vec2 {
-                            x: new_pos.x as f32,
-                            y: new_pos.y as f32,
-                        },
-                    ),
-                    duration: 0.35,
-                    parent: None,
-                });
-                if rot != &Rotation::None {
-                    commands.spawn(Animation {
-                        animation_type: AnimationType::Rotate(
+        // Each step waits for the one before it, so the card plays out in order
+        let mut previous = None;
+        for step in play.steps.iter() {
+            match step {
+                EffectStep::Moved(to) => {
+                    let animation = Animation {
+                        animation_type: AnimationType::Move(
                             player_id,
-                            new_pos.rotated(&rot).d.get_quat(),
+                            Vec2 {
+                                x: to.x as f32,
+                                y: to.y as f32,
+                            },
                         ),
                         duration: 0.35,
-                        parent: None,
-                    });
+                        parent: previous,
+                    };
+                    previous = Some(commands.spawn(animation).id());
                 }
-                commands.spawn(Change {
-                    entity: player_id,
-                    updated_value: new_pos.rotated(&rot).clone(),
-                });
-            }
-        }
-        match &card_info.water_damage {
-            DamageInfo {
-                damage_target: target,
-                amount: amnt,
-            } if *amnt > 0 => {
-                let grid = grid.get_single().expect("Failed to get grid");
-                let base_pos = game_positions
-                    .get(player_id)
-                    .expect("Failed to get player position");
-                let target_positions = target.get_positions(base_pos);
-                for pos in target_positions.iter() {
-                    if let Some(tile_id) = grid.get(pos) {
-                        info!("Spawning turn blue animation");
+                EffectStep::Rotated(to) => {
+                    let animation = Animation {
+                        animation_type: AnimationType::Rotate(player_id, to.d.get_quat()),
+                        duration: 0.35,
+                        parent: previous,
+                    };
+                    previous = Some(commands.spawn(animation).id());
+                }
+                EffectStep::Sprayed {
+                    targets,
+                    extinguished,
+                } => {
+                    let mut last = previous;
+                    for pos in targets.iter() {
+                        let Some(tile_id) = grid.get(pos) else {
+                            continue;
+                        };
                         let blue_id = commands
                             .spawn(Animation {
                                 animation_type: AnimationType::Blue(tile_id),
                                 duration: 0.35,
-                                parent: None,
+                                parent: previous,
                             })
                             .id();
-                        if let Tile::Fire(_) = tiles.get(tile_id).unwrap() {
-                            commands.spawn(Change {
-                                entity: tile_id,
-                                updated_value: Tile::Empty,
-                            });
+                        if extinguished
+                            .iter()
+                            .any(|out| out.x == pos.x && out.y == pos.y)
+                        {
                             commands.spawn(Animation {
                                 animation_type: AnimationType::Smoke(tile_id),
                                 duration: 0.35,
                                 parent: Some(blue_id),
                             });
                         }
+                        last = Some(blue_id);
                     }
+                    previous = last;
                 }
+                EffectStep::WallsPlaced(walls) => info!("Building {} wall(s)", walls.len()),
+                EffectStep::DoorsOpened(doors) => info!("Opening {} door(s)", doors.len()),
             }
-            _ => {}
+        }
+        commands.spawn(Change {
+            entity: player_id,
+            updated_value: play.to.clone(),
+        });
+        for pos in board.positions() {
+            let after = play.board.get(&pos);
+            if board.get(&pos) == after {
+                continue;
+            }
+            if let (Some(tile_id), Some(tile)) = (grid.get(&pos), after) {
+                commands.spawn(Change {
+                    entity: tile_id,
+                    updated_value: tile.clone(),
+                });
+            }
+        }
+        let discards = apply_hand_effects(
+            card_instance_id,
+            &card_info.effects,
+            &mut hand,
+            &mut deck,
+            &mut *rng,
+        );
+        if discards > 0 {
+            commands.insert_resource(PendingDiscard(discards));
         }
         turn_state.set(TurnState::Animating);
         commands.entity(was_played_id).despawn_recursive();
@@ -247,6 +301,17 @@ pub enum AnimationType {
     Rotate(Entity, Quat),
     Move(Entity, Vec2),
 }
+
+impl AnimationType {
+    pub fn target(&self) -> Entity {
+        match self {
+            AnimationType::Blue(target_id) => *target_id,
+            AnimationType::Smoke(target_id) => *target_id,
+            AnimationType::Rotate(target_id, _) => *target_id,
+            AnimationType::Move(target_id, _) => *target_id,
+        }
+    }
+}
 #[derive(Component)]
 pub struct Animation {
     pub animation_type: AnimationType,
@@ -272,27 +337,97 @@ pub fn animate_cards(
             continue;
         }
         animation.duration -= time.delta_seconds();
-        let target_id = match &animation.animation_type {
-            AnimationType::Blue(target_id) => target_id,
-            AnimationType::Smoke(target_id) => target_id,
-            AnimationType::Rotate(target_id, _) => target_id,
-            AnimationType::Move(target_id, _) => target_id,
-        };
+        let target_id = animation.animation_type.target();
         if animation.duration <= 0.0 {
             finished_animations.push(animation_id);
             commands.entity(animation_id).despawn_recursive();
-            commands.entity(*target_id).remove::<Animating>();
+            commands.entity(target_id).remove::<Animating>();
         } else {
-            commands.entity(*target_id).insert(Animating(animation_id));
+            commands.entity(target_id).insert(Animating(animation_id));
         }
     }
     for entity in finished_animations.iter() {
-        for (_, mut animation) in animations.iter_mut() {
+        for (animation_id, mut animation) in animations.iter_mut() {
             if let Some(parent) = animation.parent {
                 if parent == *entity {
                     animation.parent = None;
+                    // Hand the target straight over so it doesn't snap to its end state in between
+                    commands
+                        .entity(animation.animation_type.target())
+                        .insert(Animating(animation_id));
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(index: u32) -> Entity {
+        Entity::from_raw(index)
+    }
+
+    fn deck(cards: &[u32]) -> Deck {
+        Deck {
+            cards: cards.iter().map(|index| card(*index)).collect(),
+            recycled: Vec::new(),
+            discarded: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn drawing_into_a_full_hand_keeps_the_card_in_the_deck() {
+        let mut hand = Hand([1, 2, 3, 4, 5].map(|index| Some(card(index))));
+        let mut deck = deck(&[6]);
+        let mut rng = GameRng::new(1);
+        let discards = apply_hand_effects(
+            card(0),
+            &[CardEffect::Draw(1)],
+            &mut hand,
+            &mut deck,
+            &mut rng,
+        );
+        assert_eq!(discards, 0);
+        assert_eq!(deck.cards, vec![card(6)]);
+        assert_eq!(hand.empty_slots(), 0);
+    }
+
+    #[test]
+    fn hand_effects_apply_in_card_order() {
+        let mut hand = Hand([Some(card(1)), Some(card(2)), None, None, None]);
+        let mut deck = deck(&[3, 4, 5]);
+        deck.recycle(card(0));
+        let mut rng = GameRng::new(1);
+        let discards = apply_hand_effects(
+            card(0),
+            &[CardEffect::RecycleHand, CardEffect::Draw(3), CardEffect::Discard(1)],
+            &mut hand,
+            &mut deck,
+            &mut rng,
+        );
+        assert_eq!(discards, 1);
+        // The recycled hand only comes back once the deck runs out
+        assert_eq!(hand.0, [Some(card(5)), Some(card(4)), Some(card(3)), None, None]);
+        assert_eq!(deck.cards, Vec::<Entity>::new());
+        assert_eq!(deck.recycled, vec![card(0), card(1), card(2)]);
+    }
+
+    #[test]
+    fn exhausted_cards_are_not_drawn_again() {
+        let mut hand = Hand::default();
+        let mut deck = deck(&[]);
+        deck.recycle(card(0));
+        let mut rng = GameRng::new(1);
+        apply_hand_effects(
+            card(0),
+            &[CardEffect::Exhaust, CardEffect::Draw(1)],
+            &mut hand,
+            &mut deck,
+            &mut rng,
+        );
+        assert_eq!(hand.empty_slots(), HAND_SIZE);
+        assert_eq!(deck.discarded, vec![card(0)]);
+    }
+}
@@ -4,6 +4,9 @@ pub struct SpawnCard {
     pub content_id: ContentID,
 }
 
+pub const CARD_TEXTURE_COLUMNS: usize = 5;
+pub const CARD_TEXTURE_ROWS: usize = 4;
+
 #[derive(Resource)]
 pub struct CardSpriteSheet(pub Handle<TextureAtlas>);
 
@@ -14,7 +17,14 @@ impl FromWorld for CardSpriteSheet {
             .expect("Failed get the `AssetServer` resource from the `World`");
         let texture_handle = asset_server.load("cards.png");
         let texture_atlas =
-            TextureAtlas::from_grid(texture_handle, Vec2::new(160.0, 160.0), 5, 4, None, None);
+            TextureAtlas::from_grid(
+            texture_handle,
+            Vec2::new(160.0, 160.0),
+            CARD_TEXTURE_COLUMNS,
+            CARD_TEXTURE_ROWS,
+            None,
+            None,
+        );
         let mut texture_atlases = world
             .get_resource_mut::<Assets<TextureAtlas>>()
             .expect("Failed get the `Assets<TextureAtlas>` resource from the `World`");
@@ -0,0 +1,297 @@
+use super::*;
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub const CARD_DEFINITIONS_PATH: &str = "base.cards.ron";
+
+#[derive(Deserialize)]
+pub struct CardDefinition {
+    pub id: usize,
+    pub name: String,
+    pub description: String,
+    pub resource_cost: ResourceInfo,
+    pub texture_index: usize,
+    #[serde(default)]
+    pub effects: Vec<CardEffect>,
+    #[serde(default)]
+    pub upgrades: Vec<CardUpgrade>,
+}
+
+impl CardDefinition {
+    /// Checks the definition and returns every problem found with it.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.id == 0 {
+            errors.push("id must be at least 1".to_string());
+        }
+        if self.name.trim().is_empty() {
+            errors.push("name is empty".to_string());
+        }
+        if self.resource_cost.energy < 0 || self.resource_cost.water < 0 {
+            errors.push("resource costs cannot be negative".to_string());
+        }
+        if self.texture_index >= CARD_TEXTURE_COLUMNS * CARD_TEXTURE_ROWS {
+            errors.push(format!(
+                "texture_index {} is outside of the card sprite sheet",
+                self.texture_index
+            ));
+        }
+        errors.extend(effect_errors(&self.effects));
+        for (index, upgrade) in self.upgrades.iter().enumerate() {
+            if let Some(cost) = &upgrade.resource_cost {
+                if cost.energy < 0 || cost.water < 0 {
+                    errors.push(format!("upgrade {} has a negative resource cost", index + 1));
+                }
+            }
+            if let Some(effects) = &upgrade.effects {
+                for error in effect_errors(effects) {
+                    errors.push(format!("upgrade {} {}", index + 1, error));
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// Problems with a list of card effects that would otherwise fail at play time.
+fn effect_errors(effects: &[CardEffect]) -> Vec<String> {
+    let no_offsets = |target: &TileTarget| {
+        matches!(target, TileTarget::FacingOffsets(offsets) if offsets.is_empty())
+    };
+    let mut errors = Vec::new();
+    for effect in effects.iter() {
+        match effect {
+            CardEffect::Move(target) if no_offsets(target) => {
+                errors.push("Move needs at least one offset".to_string());
+            }
+            CardEffect::PlaceWall(target) if no_offsets(target) => {
+                errors.push("PlaceWall needs at least one offset".to_string());
+            }
+            CardEffect::OpenDoor(target) if no_offsets(target) => {
+                errors.push("OpenDoor needs at least one offset".to_string());
+            }
+            CardEffect::Extinguish(damage)
+                if damage.amount == 0 || no_offsets(&damage.damage_target) =>
+            {
+                errors.push("Extinguish needs an amount and at least one offset".to_string());
+            }
+            CardEffect::Gain(gain) if gain.energy < 0 || gain.water < 0 => {
+                errors.push("Gain cannot be negative".to_string());
+            }
+            CardEffect::Draw(0) | CardEffect::Discard(0) => {
+                errors.push("effects cannot draw or discard 0 cards".to_string());
+            }
+            _ => {}
+        }
+    }
+    errors
+}
+
+impl From<&CardDefinition> for CardInfo {
+    fn from(definition: &CardDefinition) -> Self {
+        Self {
+            resource_cost: definition.resource_cost.clone(),
+            texture_index: definition.texture_index,
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            effects: definition.effects.clone(),
+            upgrades: definition.upgrades.clone(),
+        }
+    }
+}
+
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(transparent)]
+pub struct CardDefinitions(pub Vec<CardDefinition>);
+
+impl CardDefinitions {
+    /// Builds the card infos, reporting and leaving out every invalid card.
+    pub fn card_info_map(&self) -> CardInfoMap {
+        let mut card_infos = HashMap::new();
+        for definition in self.0.iter() {
+            let mut errors = definition.validate();
+            if card_infos.contains_key(&ContentID(definition.id)) {
+                errors.push("id is already used by another card".to_string());
+            }
+            if !errors.is_empty() {
+                for error in errors.iter() {
+                    error!("Card {} ({}): {}", definition.id, definition.name, error);
+                }
+                continue;
+            }
+            card_infos.insert(ContentID(definition.id), definition.into());
+        }
+        info!("Loaded {} card definitions", card_infos.len());
+        CardInfoMap(card_infos)
+    }
+}
+
+#[derive(Default)]
+pub struct CardDefinitionsLoader;
+
+#[derive(Debug, Error)]
+pub enum CardDefinitionsLoaderError {
+    #[error("Could not read card definitions: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse card definitions: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for CardDefinitionsLoader {
+    type Asset = CardDefinitions;
+    type Settings = ();
+    type Error = CardDefinitionsLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<CardDefinitions>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cards.ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct CardDefinitionsHandle(pub Handle<CardDefinitions>);
+
+impl FromWorld for CardDefinitionsHandle {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world
+            .get_resource::<AssetServer>()
+            .expect("Failed get the `AssetServer` resource from the `World`");
+        Self(asset_server.load(CARD_DEFINITIONS_PATH))
+    }
+}
+
+/// Rebuilds the `CardInfoMap` whenever the card definitions finish loading or
+/// are modified on disk. Invalid cards are reported and left out of the map.
+pub fn load_card_infos(
+    mut events: EventReader<AssetEvent<CardDefinitions>>,
+    definitions: Res<Assets<CardDefinitions>>,
+    handle: Res<CardDefinitionsHandle>,
+    mut map: ResMut<CardInfoMap>,
+) {
+    let mut changed = false;
+    for event in events.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            changed = true;
+        }
+    }
+    if !changed {
+        return;
+    }
+    let Some(definitions) = definitions.get(&handle.0) else {
+        return;
+    };
+    *map = definitions.card_info_map();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A card that passes validation, with any of its fields swapped out or
+    /// extra ones added, e.g. `"id: 0"`.
+    fn definition(changes: &str) -> CardDefinition {
+        let mut fields = vec![
+            "id: 1".to_string(),
+            "name: \"Spray\"".to_string(),
+            "description: \"\"".to_string(),
+            "resource_cost: (energy: 1, water: 1)".to_string(),
+            "texture_index: 0".to_string(),
+            "effects: [Extinguish((damage_target: FacingDist(1), amount: 1))]".to_string(),
+        ];
+        for change in changes.split(';').filter(|change| !change.trim().is_empty()) {
+            let key = change.split(':').next().unwrap_or_default().trim();
+            fields.retain(|field| !field.starts_with(&format!("{}:", key)));
+            fields.push(change.trim().to_string());
+        }
+        ron::from_str(&format!("({})", fields.join(", "))).expect("Card should parse")
+    }
+
+    fn errors(changes: &str) -> Vec<String> {
+        definition(changes).validate()
+    }
+
+    #[test]
+    fn accepts_a_valid_card() {
+        assert!(errors("").is_empty());
+        let map = CardDefinitions(vec![definition("")]).card_info_map();
+        assert_eq!(map.0[&ContentID(1)].name, "Spray");
+    }
+
+    #[test]
+    fn rejects_bad_card_fields() {
+        assert_eq!(errors("id: 0"), vec!["id must be at least 1"]);
+        assert_eq!(errors("name: \" \""), vec!["name is empty"]);
+        assert_eq!(
+            errors("resource_cost: (energy: -1, water: 0)"),
+            vec!["resource costs cannot be negative"]
+        );
+        assert_eq!(
+            errors("texture_index: 20"),
+            vec!["texture_index 20 is outside of the card sprite sheet"]
+        );
+    }
+
+    #[test]
+    fn rejects_effects_that_would_fail_at_play_time() {
+        assert_eq!(
+            errors("effects: [Move(FacingOffsets([]))]"),
+            vec!["Move needs at least one offset"]
+        );
+        assert_eq!(
+            errors("effects: [Extinguish((damage_target: FacingDist(1), amount: 0))]"),
+            vec!["Extinguish needs an amount and at least one offset"]
+        );
+        assert_eq!(
+            errors("effects: [Gain((energy: -1, water: 0))]"),
+            vec!["Gain cannot be negative"]
+        );
+        assert_eq!(
+            errors("effects: [Draw(0)]"),
+            vec!["effects cannot draw or discard 0 cards"]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_upgrades() {
+        assert_eq!(
+            errors("upgrades: [(resource_cost: Some((energy: -1, water: 0)))]"),
+            vec!["upgrade 1 has a negative resource cost"]
+        );
+        assert_eq!(
+            errors("upgrades: [(), (effects: Some([Discard(0)]))]"),
+            vec!["upgrade 2 effects cannot draw or discard 0 cards"]
+        );
+    }
+
+    #[test]
+    fn leaves_out_invalid_and_duplicate_cards() {
+        let map = CardDefinitions(vec![
+            definition(""),
+            definition("name: \"Copy\""),
+            definition("id: 2; texture_index: 99"),
+        ])
+        .card_info_map();
+        assert_eq!(map.0.len(), 1);
+        assert_eq!(map.0[&ContentID(1)].name, "Spray");
+    }
+
+    #[test]
+    fn malformed_files_are_an_error() {
+        assert!(ron::from_str::<CardDefinitions>("[(id: 1, name: \"Spray\")]").is_err());
+        assert!(ron::from_str::<CardDefinitions>("[(id: 1,").is_err());
+    }
+}
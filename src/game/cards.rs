@@ -1,4 +1,5 @@
 use super::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component, Debug)]
 pub struct InHand;
@@ -6,8 +7,10 @@ pub struct InHand;
 #[derive(Component)]
 pub struct InDeck;
 
-#[derive(Component, Default)]
-pub struct Hand(pub [Option<Entity>; 5]);
+pub const HAND_SIZE: usize = 5;
+
+#[derive(Clone, Component, Default)]
+pub struct Hand(pub [Option<Entity>; HAND_SIZE]);
 
 impl Hand {
     pub fn add(&mut self, card: Entity) {
@@ -29,9 +32,19 @@ impl Hand {
     pub fn empty_slots(&self) -> usize {
         self.0.iter().filter(|&slot| slot.is_none()).count()
     }
+    /// Draws the next card from the deck. A full hand draws nothing, so the
+    /// card stays in the deck instead of being lost.
+    pub fn draw<R: Rng + ?Sized>(&mut self, deck: &mut Deck, rng: &mut R) {
+        if self.empty_slots() == 0 {
+            return;
+        }
+        if let Some(card) = deck.draw(rng) {
+            self.add(card);
+        }
+    }
 
     pub fn reset(&mut self) {
-        self.0 = [None; 5];
+        self.0 = [None; HAND_SIZE];
     }
 }
 
@@ -53,7 +66,7 @@ pub fn sync_hand(
     }
 }
 
-#[derive(Component)]
+#[derive(Clone, Component)]
 pub struct Deck {
     pub cards: Vec<Entity>,
     pub recycled: Vec<Entity>,
@@ -61,20 +74,18 @@ pub struct Deck {
 }
 
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 
 impl Deck {
     pub fn add(&mut self, card: Entity) {
         self.cards.push(card);
     }
-    pub fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
-    pub fn draw(&mut self) -> Option<Entity> {
+    pub fn draw<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<Entity> {
         if self.cards.is_empty() {
             self.cards = self.recycled.drain(..).collect();
-            self.shuffle();
+            self.shuffle(rng);
         }
         self.cards.pop()
     }
@@ -120,12 +131,13 @@ pub fn fill_hand_with_cards(
 #[derive(Component)]
 pub struct BlockedTile;
 
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
 pub struct ResourceInfo {
     pub energy: i32,
     pub water: i32,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
 pub enum Rotation {
     None,
     Left,
@@ -133,35 +145,109 @@ pub enum Rotation {
     Reverse,
 }
 
-pub struct MovementInfo {
-    pub position: TileTarget,
-    pub rotation: Rotation,
-}
-
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
 pub struct DamageInfo {
     pub damage_target: TileTarget,
     pub amount: u32,
 }
 
-impl DamageInfo {
-    pub fn none() -> Self {
-        Self {
-            damage_target: TileTarget::FacingDist(0),
-            amount: 0,
-        }
-    }
+/// One step of what a card does once it has been played. A card's effects run
+/// in order, each from wherever the player is by then.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub enum CardEffect {
+    /// Move to the target tile. The card can't be played unless the way there is clear.
+    Move(TileTarget),
+    /// Turn on the spot.
+    Rotate(Rotation),
+    /// Step forward until the next tile is a wall or door.
+    MoveUntilBlocked,
+    /// Spray water on the target tiles.
+    Extinguish(DamageInfo),
+    /// Gain energy and water.
+    Gain(ResourceInfo),
+    /// Build a wall on each empty target tile.
+    PlaceWall(TileTarget),
+    /// Open each closed door on the target tiles.
+    OpenDoor(TileTarget),
+    /// Draw this many cards.
+    Draw(u32),
+    /// The player picks this many cards in their hand to discard.
+    Discard(u32),
+    /// Every other card in the hand goes to the recycle pile.
+    RecycleHand,
+    /// The card is discarded instead of recycled, so it won't come back this level.
+    Exhaust,
 }
 
-#[derive(Component)]
+/// One step up from a card's base stats. Anything left out stays the same.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CardUpgrade {
+    pub description: Option<String>,
+    pub resource_cost: Option<ResourceInfo>,
+    pub effects: Option<Vec<CardEffect>>,
+}
+
+#[derive(Clone, Component)]
 pub struct CardInfo {
     pub resource_cost: ResourceInfo,
-    pub position_change: MovementInfo,
-    pub water_damage: DamageInfo,
     pub texture_index: usize,
     pub name: String,
     pub description: String,
+    pub effects: Vec<CardEffect>,
+    pub upgrades: Vec<CardUpgrade>,
+}
+
+impl CardInfo {
+    /// The most water damage any one of the card's sprays does.
+    pub fn water_damage(&self) -> u32 {
+        self.effects
+            .iter()
+            .filter_map(|effect| match effect {
+                CardEffect::Extinguish(damage) => Some(damage.amount),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The description, plus how hard the card hits fires.
+    pub fn full_description(&self) -> String {
+        match self.water_damage() {
+            0 => self.description.clone(),
+            1 => format!("{}\nLowers fires by 1 level", self.description),
+            damage => format!("{}\nLowers fires by {} levels", self.description, damage),
+        }
+    }
+
+    pub fn max_upgrade(&self) -> u32 {
+        self.upgrades.len() as u32
+    }
+
+    /// The card's stats with the first `level` upgrades applied, named with a
+    /// `+` for each one.
+    pub fn upgraded(&self, level: u32) -> CardInfo {
+        let mut card_info = self.clone();
+        for upgrade in self.upgrades.iter().take(level as usize) {
+            if let Some(description) = &upgrade.description {
+                card_info.description = description.clone();
+            }
+            if let Some(resource_cost) = &upgrade.resource_cost {
+                card_info.resource_cost = resource_cost.clone();
+            }
+            if let Some(effects) = &upgrade.effects {
+                card_info.effects = effects.clone();
+            }
+            card_info.name.push('+');
+        }
+        card_info
+    }
 }
 
+/// How many times a card instance has been upgraded.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Eq)]
+pub struct Upgrade(pub u32);
+
 #[derive(Component)]
 pub struct AssetInfo {
     pub sprite_sheet_name: String,
@@ -187,6 +273,22 @@ pub struct Damageable(pub Vec<Entity>);
 pub struct Grid(pub Vec<Vec<Entity>>);
 
 impl Grid {
+    /// Copies the tile state out of the ECS so the rules can be run on it.
+    pub fn board(&self, tile: impl Fn(Entity) -> Option<Tile>) -> Board {
+        Board {
+            tiles: self
+                .0
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|id| tile(*id).unwrap_or(Tile::Wall))
+                        .collect()
+                })
+                .collect(),
+            high_turns: HashMap::new(),
+        }
+    }
+
     pub fn get(&self, pos: &GamePosition) -> Option<Entity> {
         if pos.y < 0 || pos.x < 0 {
             return None;
@@ -196,42 +298,14 @@ impl Grid {
         }
         Some(self.0[pos.y as usize][pos.x as usize])
     }
-
-    pub fn neighbors(&self, pos: &GamePosition) -> Vec<Entity> {
-        let mut entities = Vec::new();
-        for x in -1..=1 {
-            for y in -1..=1 {
-                if x == 0 && y == 0 {
-                    continue;
-                }
-                let neighbor = GamePosition {
-                    x: pos.x + x,
-                    y: pos.y + y,
-                    ..pos.clone()
-                };
-                if neighbor.x < 0 || neighbor.y < 0 {
-                    continue;
-                }
-                if neighbor.x as usize >= self.0.len() || neighbor.y as usize >= self.0[0].len() {
-                    continue;
-                }
-                if (x.abs() + y.abs()) > 1 {
-                    continue;
-                }
-                if let Some(ent) = self.get(&neighbor) {
-                    entities.push(ent);
-                }
-            }
-        }
-        entities
-    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
 pub struct Offset {
     pub facing: i32,
     pub tangent: i32,
 }
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
 pub enum TileTarget {
     FacingDist(i32),
     FacingOffsets(Vec<Offset>),
@@ -255,7 +329,7 @@ impl TileTarget {
 }
 
 // ContentID is useful when trying to serialize/deserialize the game state
-#[derive(Clone, Component, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Component, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct ContentID(pub usize);
 
 pub fn despawn_card_infos(mut commands: Commands, card_infos: Query<Entity, With<ContentID>>) {
@@ -267,615 +341,27 @@ pub fn despawn_card_infos(mut commands: Commands, card_infos: Query<Entity, With
 #[derive(Default, Resource)]
 pub struct CardInfoMap(pub HashMap<ContentID, CardInfo>);
 
-pub fn load_card_infos(mut map: ResMut<CardInfoMap>) {
-    let mut card_infos = HashMap::new();
-    // Forward
-    card_infos.insert(
-        ContentID(1),
-        CardInfo {
-            name: "Forward".to_string(),
-            description: "Move forward 1 tile".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(1),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 0,
-        },
-    );
-    // Backward
-    card_infos.insert(
-        ContentID(2),
-        CardInfo {
-            name: "Backward".to_string(),
-            description: "Move backward 1 tile".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(-1),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 1,
-        },
-    );
-    // Face Right
-    card_infos.insert(
-        ContentID(3),
-        CardInfo {
-            name: "Right".to_string(),
-            description: "Rotate facing direction to the right".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::Right,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 2,
-        },
-    );
-    // Face Left
-    card_infos.insert(
-        ContentID(4),
-        CardInfo {
-            name: "Left".to_string(),
-            description: "Rotate facing direction to the left".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::Left,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 3,
-        },
-    );
-    card_infos.insert(
-        ContentID(5),
-        CardInfo {
-            name: "Squirt".to_string(),
-            description: "Extinguish fire 1 tile away in facing direction".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 1,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingDist(1),
-                amount: 1,
-            },
-            texture_index: 4,
-        },
-    );
-    card_infos.insert(
-        ContentID(6),
-        CardInfo {
-            name: "Splash".to_string(),
-            description:
-                "Extinguish up to 3 fires in a row that are 2 tiles away in facing direction"
-                    .to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 1,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: -1,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: 1,
-                        facing: 2,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 5,
-        },
-    );
-    card_infos.insert(
-        ContentID(7),
-        CardInfo {
-            name: "Sprinkle".to_string(),
-            description: "Extinguish fire 1 tile away in each cardinal direction".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 2,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: -1,
-                        facing: 0,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: 1,
-                        facing: 0,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: -1,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 6,
-        },
-    );
-    card_infos.insert(
-        ContentID(8),
-        CardInfo {
-            name: "Spray".to_string(),
-            description: "Extinguish fire in a cone in the facing direction".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 3,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 0,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: 1,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: -2,
-                        facing: 3,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 3,
-                    },
-                    Offset {
-                        tangent: 2,
-                        facing: 3,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 7,
-        },
-    );
-    card_infos.insert(
-        ContentID(9),
-        CardInfo {
-            name: "Slosh".to_string(),
-            description: "Move forward and extinguish 2 tiles adjacent to the destination"
-                .to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 1,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(1),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 1,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: 1,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 8,
-        },
-    );
-    card_infos.insert(
-        ContentID(10),
-        CardInfo {
-            name: "Water Jet".to_string(),
-            description: "Water propels you forward and extinguishes some trailing tiles"
-                .to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 2,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(1),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 1,
-                        facing: -1,
-                    },
-                    Offset {
-                        tangent: 2,
-                        facing: -2,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: -1,
-                    },
-                    Offset {
-                        tangent: -2,
-                        facing: -2,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 9,
-        },
-    );
-    card_infos.insert(
-        ContentID(11),
-        CardInfo {
-            name: "Wave Turn Right".to_string(),
-            description:
-                "Turn right and extinguish a row of tiles in your original facing direction"
-                    .to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 1,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::Right,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 1,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: 1,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 10,
-        },
-    );
-    card_infos.insert(
-        ContentID(12),
-        CardInfo {
-            name: "Wave Turn Left".to_string(),
-            description:
-                "Turn left and extinguish a row of tiles in your original facing direction"
-                    .to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 1,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::Left,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 1,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: 1,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 11,
-        },
-    );
-    card_infos.insert(
-        ContentID(13),
-        CardInfo {
-            name: "Spin and Spray".to_string(),
-            description: "Reverse facing direction and extinguish tiles at each corner".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 1,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::Reverse,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 1,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: 1,
-                        facing: -1,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: -1,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 12,
-        },
-    );
-    card_infos.insert(
-        ContentID(14),
-        CardInfo {
-            name: "Back Blast".to_string(),
-            description: "Extinguish 3 tiles in facing direction and move backward one tile"
-                .to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 2,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(-1),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 0,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 3,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 13,
-        },
-    );
-    card_infos.insert(
-        ContentID(15),
-        CardInfo {
-            name: "Expell".to_string(),
-            description: "Extinguish 2 tiles from each diagonal".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 2,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 1,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: 2,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: -2,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: 1,
-                        facing: -1,
-                    },
-                    Offset {
-                        tangent: 2,
-                        facing: -2,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: -1,
-                    },
-                    Offset {
-                        tangent: -2,
-                        facing: -2,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 14,
-        },
-    );
-    card_infos.insert(
-        ContentID(16),
-        CardInfo {
-            name: "Cross Crash".to_string(),
-            description: "Extinguish 2 tiles in each cardinal direction".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 0,
-                water: 3,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingDist(0),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo {
-                damage_target: TileTarget::FacingOffsets(vec![
-                    Offset {
-                        tangent: 0,
-                        facing: 1,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: 2,
-                    },
-                    Offset {
-                        tangent: -1,
-                        facing: 0,
-                    },
-                    Offset {
-                        tangent: -2,
-                        facing: 0,
-                    },
-                    Offset {
-                        tangent: 1,
-                        facing: 0,
-                    },
-                    Offset {
-                        tangent: 2,
-                        facing: 0,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: -1,
-                    },
-                    Offset {
-                        tangent: 0,
-                        facing: -2,
-                    },
-                ]),
-                amount: 1,
-            },
-            texture_index: 15,
-        },
-    );
-    card_infos.insert(
-        ContentID(17),
-        CardInfo {
-            name: "Forward Right".to_string(),
-            description: "Move forward and right".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingOffsets(vec![Offset {
-                    tangent: 1,
-                    facing: 1,
-                }]),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 16,
-        },
-    );
-    card_infos.insert(
-        ContentID(18),
-        CardInfo {
-            name: "Forward Left".to_string(),
-            description: "Move forward and left".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingOffsets(vec![Offset {
-                    tangent: -1,
-                    facing: 1,
-                }]),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 17,
-        },
-    );
-    card_infos.insert(
-        ContentID(19),
-        CardInfo {
-            name: "Back Left".to_string(),
-            description: "Move backward and left".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingOffsets(vec![Offset {
-                    tangent: -1,
-                    facing: -1,
-                }]),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 18,
-        },
-    );
-    card_infos.insert(
-        ContentID(20),
-        CardInfo {
-            name: "Back Right".to_string(),
-            description: "Move backward and right".to_string(),
-            resource_cost: ResourceInfo {
-                energy: 1,
-                water: 0,
-            },
-            position_change: MovementInfo {
-                position: TileTarget::FacingOffsets(vec![Offset {
-                    tangent: 1,
-                    facing: -1,
-                }]),
-                rotation: Rotation::None,
-            },
-            water_damage: DamageInfo::none(),
-            texture_index: 19,
-        },
-    );
-    *map = CardInfoMap(card_infos);
+impl CardInfoMap {
+    /// The stats a card instance plays with, after its upgrades.
+    pub fn effective(&self, content_id: &ContentID, upgrade: Option<&Upgrade>) -> Option<CardInfo> {
+        let card_info = self.0.get(content_id)?;
+        Some(card_info.upgraded(upgrade.map_or(0, |upgrade| upgrade.0)))
+    }
+}
+
+/// A card in the deck list: which card it is and how far it has been upgraded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckCard {
+    pub content_id: ContentID,
+    #[serde(default)]
+    pub upgrade: u32,
+}
+
+impl From<ContentID> for DeckCard {
+    fn from(content_id: ContentID) -> Self {
+        Self {
+            content_id,
+            upgrade: 0,
+        }
+    }
 }
@@ -0,0 +1,91 @@
+use super::*;
+
+/// Whether the fire forecast is drawn over the map. Toggled with F.
+#[derive(Default, Resource)]
+pub struct ShowForecast(pub bool);
+
+/// A tile highlight showing what the fire may do there next turn.
+#[derive(Component)]
+pub struct ForecastMarker;
+
+pub fn toggle_forecast(keyboard_input: Res<Input<KeyCode>>, mut show: ResMut<ShowForecast>) {
+    if keyboard_input.just_released(KeyCode::F) {
+        show.0 = !show.0;
+    }
+}
+
+fn marker_color(forecast: &FireForecast) -> Color {
+    match forecast {
+        FireForecast::Ignites(chance) => Color::rgba(1.0, 0.55, 0.0, 0.15 + 0.45 * chance),
+        FireForecast::Grows => Color::rgba(1.0, 0.1, 0.1, 0.4),
+        FireForecast::BurnsOut => Color::rgba(0.1, 0.1, 0.1, 0.5),
+    }
+}
+
+/// Redraws the forecast whenever it is toggled or the tiles change.
+pub fn update_forecast(
+    mut commands: Commands,
+    show: Res<ShowForecast>,
+    map: Res<MapParameters>,
+    markers: Query<Entity, With<ForecastMarker>>,
+    tiles: Query<(Ref<Tile>, Option<Ref<TurnsAtHigh>>)>,
+    grid: Query<&Grid>,
+) {
+    let tiles_changed = tiles
+        .iter()
+        .any(|(tile, turns)| tile.is_changed() || turns.is_some_and(|turns| turns.is_changed()));
+    if !show.is_changed() && !tiles_changed {
+        return;
+    }
+    for entity in markers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !show.0 {
+        return;
+    }
+    let Ok(grid) = grid.get_single() else {
+        return;
+    };
+    let mut board = grid.board(|id| tiles.get(id).ok().map(|(tile, _)| tile.clone()));
+    for pos in board.positions().collect::<Vec<_>>() {
+        if let Some(Ok((_, Some(turns)))) = grid.get(&pos).map(|id| tiles.get(id)) {
+            board.high_turns.insert((pos.x, pos.y), turns.0);
+        }
+    }
+    for (pos, forecast) in board.forecast(&map.fire) {
+        let label = match forecast {
+            FireForecast::Ignites(chance) if chance < 1.0 => {
+                format!("{:.0}%", chance * 100.0)
+            }
+            _ => String::new(),
+        };
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: marker_color(&forecast),
+                        custom_size: Some(Vec2::new(64.0, 64.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                    ..default()
+                },
+                pos,
+                ForecastMarker,
+            ))
+            .with_children(|marker| {
+                marker.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        label,
+                        TextStyle {
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..default()
+                });
+            });
+    }
+}
@@ -7,6 +7,8 @@ pub enum GameState {
     Loading,
     Loaded,
     Playing,
+    /// Between Rogue levels, while the deck is changed in the shop.
+    Shopping,
 }
 
 #[derive(Component, Default)]
@@ -54,6 +56,7 @@ impl StateTransition for GameState {
             GameState::Loading => GameState::Loaded,
             GameState::Loaded => GameState::Playing,
             GameState::Playing => GameState::Loaded,
+            GameState::Shopping => GameState::Loaded,
         }
     }
 }
@@ -0,0 +1,302 @@
+use super::*;
+use std::collections::VecDeque;
+
+/// Largest map a Rogue level grows to, in tiles across.
+const MAX_ROGUE_SIZE: i32 = 10;
+
+/// How much fire a Rogue level may hold. Low flames cost 1, medium 2 and high 3.
+pub fn fire_budget(level_index: i32) -> i32 {
+    2 + level_index.max(0) * 2
+}
+
+/// The strongest flame the budget may buy at this depth.
+fn max_intensity(level_index: i32) -> Intensity {
+    match level_index {
+        i32::MIN..=2 => Intensity::Low,
+        3..=5 => Intensity::Medium,
+        _ => Intensity::High,
+    }
+}
+
+fn cost(intensity: &Intensity) -> i32 {
+    intensity.clone() as i32
+}
+
+fn neighbors((x, y): (i32, i32)) -> [(i32, i32); 4] {
+    [(x, y + 1), (x + 1, y), (x, y - 1), (x - 1, y)]
+}
+
+/// Steps from `from` to every open tile it can walk to.
+fn distances(
+    columns: i32,
+    rows: i32,
+    walls: &[(i32, i32)],
+    from: (i32, i32),
+) -> HashMap<(i32, i32), i32> {
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::from([(from, 0)]);
+    distances.insert(from, 0);
+    while let Some((tile, distance)) = queue.pop_front() {
+        for next in neighbors(tile) {
+            let inside = next.0 >= 1 && next.1 >= 1 && next.0 <= columns && next.1 <= rows;
+            if inside && !walls.contains(&next) && !distances.contains_key(&next) {
+                distances.insert(next, distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Short wall segments scattered through bigger rooms.
+fn wall_segments(columns: i32, rows: i32, count: i32, rng: &mut GameRng) -> Vec<(i32, i32)> {
+    let mut walls = Vec::new();
+    for _ in 0..count {
+        let length = rng.gen_range(2..=3);
+        let (mut x, mut y) = (rng.gen_range(1..=columns), rng.gen_range(1..=rows));
+        let horizontal = rng.gen_bool(0.5);
+        for _ in 0..length {
+            if x <= columns && y <= rows {
+                walls.push((x, y));
+            }
+            if horizontal {
+                x += 1;
+            } else {
+                y += 1;
+            }
+        }
+    }
+    walls
+}
+
+/// Builds a Rogue level from its depth. Deeper levels are larger, more walled
+/// in and carry more and stronger fire, fuel and puddles. The start is never
+/// on or next to a flame, and every open tile can be walked to from it.
+pub fn generate_rogue_level(level_index: i32, rng: &mut GameRng) -> MapParameters {
+    let size = (3 + level_index.max(0)).min(MAX_ROGUE_SIZE);
+    let columns = size + rng.gen_range(0..=1);
+    let rows = size;
+
+    // The first levels stay open rooms while the player learns the cards
+    let corner = GamePosition {
+        x: 1,
+        y: 1,
+        d: GameDirection::Up,
+    };
+    let mut walls = if level_index < 2 {
+        Vec::new()
+    } else {
+        random_layout(columns, rows, &corner, rng)
+    };
+    if level_index >= 4 {
+        walls.extend(wall_segments(columns, rows, (columns * rows) / 25, rng));
+    }
+
+    // Start anywhere open, then wall off whatever can't be reached from there
+    let open: Vec<(i32, i32)> = (1..=rows)
+        .flat_map(|y| (1..=columns).map(move |x| (x, y)))
+        .filter(|tile| !walls.contains(tile))
+        .collect();
+    let start_tile = if level_index < 2 || open.is_empty() {
+        (1, 1)
+    } else {
+        open[rng.gen_range(0..open.len())]
+    };
+    walls.retain(|tile| *tile != start_tile);
+    let reachable = distances(columns, rows, &walls, start_tile);
+    for y in 1..=rows {
+        for x in 1..=columns {
+            if !reachable.contains_key(&(x, y)) && !walls.contains(&(x, y)) {
+                walls.push((x, y));
+            }
+        }
+    }
+    // Face the way with the most room
+    let start = [
+        GameDirection::Up,
+        GameDirection::Right,
+        GameDirection::Down,
+        GameDirection::Left,
+    ]
+    .into_iter()
+    .map(|d| GamePosition {
+        x: start_tile.0,
+        y: start_tile.1,
+        d,
+    })
+    .max_by_key(|position| {
+        (1..=MAX_ROGUE_SIZE)
+            .take_while(|dist| {
+                let ahead = position.offset((*dist, 0));
+                reachable.contains_key(&(ahead.x, ahead.y))
+            })
+            .count()
+    })
+    .expect("There are four directions");
+
+    // Spend the fire budget on clusters, seeded away from the start
+    let mut candidates: Vec<(i32, i32)> = reachable
+        .iter()
+        .filter(|(_, distance)| **distance >= 2)
+        .map(|(tile, _)| *tile)
+        .collect();
+    candidates.sort();
+    let mut flames: Vec<(i32, i32, Tile)> = Vec::new();
+    let mut budget = fire_budget(level_index);
+    let strongest = cost(&max_intensity(level_index));
+    while budget > 0 && !candidates.is_empty() {
+        let seed = candidates.swap_remove(rng.gen_range(0..candidates.len()));
+        let mut cluster = vec![seed];
+        let cluster_size = rng.gen_range(1..=3);
+        while cluster.len() < cluster_size {
+            let Some(next) = neighbors(cluster[rng.gen_range(0..cluster.len())])
+                .into_iter()
+                .find(|tile| candidates.contains(tile))
+            else {
+                break;
+            };
+            candidates.retain(|tile| *tile != next);
+            cluster.push(next);
+        }
+        for (x, y) in cluster {
+            if budget <= 0 {
+                break;
+            }
+            let intensity = match rng.gen_range(1..=strongest.min(budget)) {
+                1 => Intensity::Low,
+                2 => Intensity::Medium,
+                _ => Intensity::High,
+            };
+            budget -= cost(&intensity);
+            flames.push((x, y, Tile::Fire(intensity)));
+        }
+    }
+
+    // Deeper levels scatter puddles to refill from and fuel that flares up.
+    // Doors are left to puzzles, since nothing in a Rogue deck opens them.
+    let mut terrain: Vec<(i32, i32, Tile)> = Vec::new();
+    let puddle_count = if level_index >= 3 {
+        rng.gen_range(0..=2)
+    } else {
+        0
+    };
+    let fuel_count = if level_index >= 5 {
+        rng.gen_range(1..=level_index as usize / 3)
+    } else {
+        0
+    };
+    for tile in std::iter::repeat(Tile::Puddle)
+        .take(puddle_count)
+        .chain(std::iter::repeat(Tile::Fuel).take(fuel_count))
+    {
+        if candidates.is_empty() {
+            break;
+        }
+        let (x, y) = candidates.swap_remove(rng.gen_range(0..candidates.len()));
+        terrain.push((x, y, tile));
+    }
+
+    // Bigger fires leave more supplies lying around
+    let item_count = 1 + flames.len() as i32 / 3;
+    MapParameters {
+        columns,
+        rows,
+        start,
+        walls,
+        flame_spawner: Spawner::Static(flames),
+        item_spawner: Spawner::Chance(0.2, 1, item_count),
+        fire: fire_model(level_index, rng),
+        terrain,
+        par: None,
+    }
+}
+
+/// How the fire behaves at this depth. The first levels keep the classic
+/// rules, then each level rolls its own mix of spread, wind and burnout.
+fn fire_model(level_index: i32, rng: &mut GameRng) -> FireModel {
+    if level_index < 2 {
+        return FireModel::default();
+    }
+    let medium_spread = if level_index >= 5 {
+        rng.gen_range(0.0..=0.3)
+    } else {
+        0.0
+    };
+    let high_spread = rng.gen_range(0.6..=1.0);
+    let diagonal = level_index >= 4 && rng.gen_bool(0.3);
+    let wind = rng.gen_bool(0.5).then(|| match rng.gen_range(0..4) {
+        0 => GameDirection::Up,
+        1 => GameDirection::Right,
+        2 => GameDirection::Down,
+        _ => GameDirection::Left,
+    });
+    let burnout = rng.gen_bool(0.4).then(|| rng.gen_range(2..=4));
+    FireModel {
+        spread_chance: [0.0, medium_spread, high_spread],
+        diagonal,
+        wind,
+        burnout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(map: &MapParameters) -> Board {
+        let mut board = Board::new(map.columns, map.rows);
+        let at = |x: i32, y: i32| GamePosition { x, y, ..default() };
+        for (x, y) in map.walls.iter() {
+            board.set(&at(*x, *y), Tile::Wall);
+        }
+        for (x, y, tile) in map.terrain.iter() {
+            board.set(&at(*x, *y), tile.clone());
+        }
+        let Spawner::Static(flames) = &map.flame_spawner else {
+            panic!("Rogue flames should be placed up front");
+        };
+        for (x, y, tile) in flames.iter() {
+            board.set(&at(*x, *y), tile.clone());
+        }
+        board
+    }
+
+    #[test]
+    fn rogue_levels_start_playable() {
+        for seed in 0..20 {
+            let mut rng = GameRng::new(seed);
+            for level_index in 0..12 {
+                let map = generate_rogue_level(level_index, &mut rng);
+                let board = board(&map);
+                let start = &map.start;
+                assert!(board.is_open(start), "seed {seed} level {level_index}");
+                assert!(
+                    board
+                        .neighbors(start, false)
+                        .iter()
+                        .chain([start])
+                        .all(|pos| !matches!(board.get(pos), Some(Tile::Fire(_)))),
+                    "seed {seed} level {level_index} starts next to a flame"
+                );
+                assert_eq!(board.outcome(), Outcome::InProgress, "seed {seed} level {level_index}");
+
+                // Every open tile can be walked to from the start
+                let mut reached = vec![start.clone()];
+                let mut next = 0;
+                while next < reached.len() {
+                    for neighbor in board.neighbors(&reached[next], false) {
+                        let seen = reached
+                            .iter()
+                            .any(|pos| (pos.x, pos.y) == (neighbor.x, neighbor.y));
+                        if board.is_open(&neighbor) && !seen {
+                            reached.push(neighbor);
+                        }
+                    }
+                    next += 1;
+                }
+                let open = board.positions().filter(|pos| board.is_open(pos)).count();
+                assert_eq!(reached.len(), open, "seed {seed} level {level_index}");
+            }
+        }
+    }
+}
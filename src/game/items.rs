@@ -7,14 +7,46 @@ pub enum Item {
     Card(ContentID),
 }
 
-impl Item {
-    pub fn random(content_range: usize) -> Self {
-        match rand::random::<u8>() % 3 {
-            0 => Self::Water,
-            1 => Self::Energy,
-            2 => Self::Card(ContentID((rand::random::<usize>() % content_range) + 1)),
-            _ => unreachable!(),
+/// Relative odds of each item turning up from a `Spawner::Chance`. Cards are
+/// keyed by their `ContentID`, and cheaper cards turn up more often. Water and
+/// energy each weigh as much as all of the cards together.
+#[derive(Clone)]
+pub struct ItemTable(pub Vec<(Item, u32)>);
+
+impl ItemTable {
+    pub fn from_card_infos(card_infos: &CardInfoMap) -> Self {
+        let mut cards: Vec<(ContentID, u32)> = card_infos
+            .0
+            .iter()
+            .map(|(id, card_info)| {
+                let cost = card_info.resource_cost.energy + card_info.resource_cost.water;
+                (id.clone(), (4 - cost).clamp(1, 3) as u32)
+            })
+            .collect();
+        // Sorted so the same seed always picks the same items
+        cards.sort_by_key(|(id, _)| id.0);
+        let card_weight: u32 = cards.iter().map(|(_, weight)| weight).sum();
+        let mut entries = vec![
+            (Item::Water, card_weight.max(1)),
+            (Item::Energy, card_weight.max(1)),
+        ];
+        entries.extend(cards.into_iter().map(|(id, weight)| (Item::Card(id), weight)));
+        Self(entries)
+    }
+
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Item> {
+        let total: u32 = self.0.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0..total);
+        for (item, weight) in self.0.iter() {
+            if roll < *weight {
+                return Some(item.clone());
+            }
+            roll -= weight;
         }
+        None
     }
 }
 
@@ -87,7 +119,7 @@ pub fn apply_item(
                         ))
                         .id();
                     deck.add(card_instance_id);
-                    deck_list.0.push(content_id.clone());
+                    deck_list.0.push(content_id.clone().into());
                 }
             }
         }
@@ -95,7 +127,7 @@ pub fn apply_item(
 }
 
 #[derive(Resource)]
-pub struct ItemSpriteSheet(Handle<TextureAtlas>);
+pub struct ItemSpriteSheet(pub Handle<TextureAtlas>);
 
 impl FromWorld for ItemSpriteSheet {
     fn from_world(world: &mut World) -> Self {
@@ -3,35 +3,66 @@ use super::*;
 pub use actions::*;
 use bevy::utils::HashMap;
 pub use card::*;
+pub use card_loader::*;
 pub use cards::*;
+pub use forecast::*;
 pub use game::*;
+pub use generator::*;
 pub use items::*;
 pub use player::*;
+pub use profile::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 pub use stats::*;
 pub use tiles::*;
+pub use puzzle_loader::*;
 pub use puzzles::*;
+pub use replay::*;
+pub use rng::*;
+pub use rules::*;
+pub use save::*;
+pub use score::*;
+pub use shop::*;
+pub use solver::*;
+pub use storage::*;
+pub use undo::*;
 
 mod actions;
 mod card;
+mod card_loader;
 mod cards;
+mod forecast;
 mod game;
+mod generator;
 mod items;
 mod player;
+mod profile;
 mod stats;
 mod tiles;
+mod puzzle_loader;
 mod puzzles;
+mod replay;
+mod rng;
+mod rules;
+mod save;
+mod score;
+mod shop;
+mod solver;
+mod storage;
+mod undo;
 
-pub fn shuffle_deck(mut deck: Query<&mut Deck, With<Player>>) {
-    deck.get_single_mut().expect("Should have 1 deck").shuffle();
+pub fn shuffle_deck(mut deck: Query<&mut Deck, With<Player>>, mut rng: ResMut<GameRng>) {
+    deck.get_single_mut()
+        .expect("Should have 1 deck")
+        .shuffle(&mut *rng);
 }
 
 #[derive(Resource)]
-pub struct DeckList(pub Vec<ContentID>);
+pub struct DeckList(pub Vec<DeckCard>);
 
 impl Default for DeckList {
     fn default() -> Self {
-        Self(vec![1, 1, 2, 3, 4].iter().map(|id| ContentID(*id)).collect())
+        Self(vec![1, 1, 2, 3, 4].iter().map(|id| ContentID(*id).into()).collect())
     }
 }
 
@@ -42,10 +73,11 @@ pub fn spawn_cards(
     card_sprites: Res<CardSpriteSheet>,
 ) {
     let mut deck = deck.get_single_mut().expect("Should be exactly 1 deck");
-    for content_id in deck_list.0.iter() {
+    for card in deck_list.0.iter() {
         let card_instance_id = commands
             .spawn((
-                content_id.clone(),
+                card.content_id.clone(),
+                Upgrade(card.upgrade),
                 card_sprites.0.clone(),
                 InDeck,
                 CardStatus::Unknown,
@@ -62,37 +94,39 @@ pub fn despawn_cards(mut commands: Commands, cards: Query<Entity, With<ContentID
 }
 
 
-#[derive(Default, PartialEq, Eq, Resource, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, Resource, Clone, Copy, Serialize, Deserialize)]
 pub enum GameMode {
     #[default]
     Puzzle,
     Rogue,
 }
-fn prepare_for_rogue_level(map: &mut MapParameters, _deck_list: &mut DeckList, level_index: i32) {
-    let (c, r) = (map.columns.max(1), map.rows.max(1));
-    *map = MapParameters {
-        columns: c + 1,
-        rows: r + 1,
-        flame_spawner: Spawner::Chance(0.1, 1, level_index.max(1)),
-        item_spawner: Spawner::Chance(0.2, 1, 1),
-    };
+fn prepare_for_rogue_level(
+    map: &mut MapParameters,
+    _deck_list: &mut DeckList,
+    level_index: i32,
+    rng: &mut GameRng,
+) {
+    *map = generate_rogue_level(level_index, rng);
 }
 
-fn prepare_for_puzzle_level(map: &mut MapParameters, deck_list: &mut DeckList, level_index: i32) {
-    let level_index = (level_index as usize) % NUM_PUZZLES;
-    let level = get_puzzle(level_index);
+fn prepare_for_puzzle_level(map: &mut MapParameters, deck_list: &mut DeckList, level: &Level) {
     *map = MapParameters {
         columns: level.map_size.0,
         rows: level.map_size.1,
+        start: level.start.clone(),
+        walls: level.walls.clone(),
         flame_spawner: Spawner::Static(level.flames.iter()
-            .map(|(x, y)| (*x, *y, Tile::Fire(Intensity::Low)))
+            .map(|(x, y, intensity)| (*x, *y, Tile::Fire(intensity.clone())))
             .collect()
         ),
-        item_spawner: Spawner::Static(level.items),
+        item_spawner: Spawner::Static(level.items.clone()),
+        fire: FireModel::default(),
+        terrain: level.terrain.clone(),
+        par: level.par.clone(),
     };
     deck_list.0 = level.deck_list
         .iter()
-        .map(|id| ContentID(*id))
+        .map(|id| ContentID(*id).into())
         .collect();
 }
 
@@ -113,20 +147,31 @@ pub fn prepare_for_new_level(
     mut hand: Query<&mut Hand, With<Player>>,
     mut position: Query<&mut GamePosition, With<Player>>,
     level_index: Res<LevelIndex>,
+    puzzles: Res<Puzzles>,
+    levels: Res<Assets<Level>>,
+    playtest: Option<Res<PlaytestLevel>>,
+    mut rng: ResMut<GameRng>,
 ) {
     match *mode {
+        GameMode::Puzzle if playtest.is_some() => {
+            let level = &playtest.expect("Checked above").0;
+            prepare_for_puzzle_level(&mut map, &mut deck_list, level);
+        }
         GameMode::Puzzle => {
-            prepare_for_puzzle_level(&mut map, &mut deck_list, level_index.0);
+            let level = puzzles
+                .get(level_index.0 as usize, &levels)
+                .expect("Puzzles should be loaded");
+            prepare_for_puzzle_level(&mut map, &mut deck_list, level);
+        }
+        GameMode::Rogue => {
+            prepare_for_rogue_level(&mut map, &mut deck_list, level_index.0, &mut rng)
         }
-        GameMode::Rogue => prepare_for_rogue_level(&mut map, &mut deck_list, level_index.0),
     }
     // Reset the transitory player state
     let mut position = position
         .get_single_mut()
         .expect("Should be exactly 1 player");
-    position.x = 1;
-    position.y = 1;
-    position.d = GameDirection::Up;
+    *position = map.start.clone();
 
     // Update the deck list
 
@@ -154,45 +199,39 @@ pub fn start_turn(
 
 fn restore_resources(mut energy: Query<(&mut Energy, &mut Water), With<Player>>) {
     let (mut energy, mut water) = energy.get_single_mut().expect("Should be exactly 1 energy");
-    energy.current = energy.maxium / 2;
-    water.current = water.maxium / 2;
-}
-
-fn grow_flames(mut tiles: Query<&mut Tile>) {
-    for mut tile in tiles.iter_mut() {
-        match *tile {
-            Tile::Fire(Intensity::Low) => {
-                *tile = Tile::Fire(Intensity::Medium);
-            }
-            Tile::Fire(Intensity::Medium) => {
-                *tile = Tile::Fire(Intensity::High);
-            }
-            _ => {}
-        }
-    }
+    restore_for_turn(&mut energy, &mut water);
 }
 
-fn propagate_flames(
+fn advance_fire(
     mut commands: Commands,
-    tiles: Query<(Entity, &Tile)>,
-    positions: Query<&GamePosition>,
+    map: Res<MapParameters>,
+    mut rng: ResMut<GameRng>,
+    tiles: Query<(&Tile, Option<&TurnsAtHigh>)>,
     grid: Query<&Grid>,
 ) {
     let grid = grid.get_single().expect("Failed to get grid");
-    for (tile_id, tile) in tiles.iter() {
-        if let Tile::Fire(Intensity::High) = tile {
-            let position = positions.get(tile_id).expect("Failed to get position");
-            for neighbor in grid.neighbors(position) {
-                if let Ok((neighbor_id, neighbor_tile)) = tiles.get(neighbor) {
-                    if let Tile::Empty = *neighbor_tile {
-                        commands
-                            .entity(neighbor_id)
-                            .insert(Tile::Fire(Intensity::Low));
-                    }
-                }
-            }
+    let mut board = grid.board(|id| tiles.get(id).ok().map(|(tile, _)| tile.clone()));
+    for pos in board.positions().collect::<Vec<_>>() {
+        if let Some(Ok((_, Some(turns)))) = grid.get(&pos).map(|id| tiles.get(id)) {
+            board.high_turns.insert((pos.x, pos.y), turns.0);
         }
     }
+    board.advance_fire(&map.fire, &mut *rng);
+    for pos in board.positions() {
+        let (Some(tile_id), Some(next)) = (grid.get(&pos), board.get(&pos)) else {
+            continue;
+        };
+        let Ok((tile, _)) = tiles.get(tile_id) else {
+            continue;
+        };
+        if tile != next {
+            commands.entity(tile_id).insert(next.clone());
+        }
+        match board.high_turns.get(&(pos.x, pos.y)) {
+            Some(turns) => commands.entity(tile_id).insert(TurnsAtHigh(*turns)),
+            None => commands.entity(tile_id).remove::<TurnsAtHigh>(),
+        };
+    }
 }
 
 fn check_for_level_end(
@@ -200,51 +239,75 @@ fn check_for_level_end(
     mut next_turn_state: ResMut<NextState<TurnState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut level_index: ResMut<LevelIndex>,
-    tiles: Query<&Tile>,
-    changes: Query<Entity, Changed<Tile>>,
+    (mode, playback): (Res<GameMode>, Option<Res<Playback>>),
+    tiles: Query<Ref<Tile>>,
+    grid: Query<&Grid>,
 ) {
-    if changes.is_empty() {
+    if !tiles.iter().any(|tile| tile.is_changed()) {
         return;
     }
-    let mut fire_count = 0;
-    let mut empty_count = 0;
-    for tile in tiles.iter() {
-        match tile {
-            Tile::Fire(_) => fire_count += 1,
-            Tile::Empty => empty_count += 1,
-            _ => {}
+    let Ok(grid) = grid.get_single() else {
+        return;
+    };
+    let board = grid.board(|id| tiles.get(id).ok().map(|tile| tile.clone()));
+    match board.outcome() {
+        Outcome::Failure => {
+            info!("Level ended | Failure");
+            // A replayed loss isn't the player's run, so their save stays
+            if *mode == GameMode::Rogue && playback.is_none() {
+                RogueSave::delete();
+            }
+            // In puzzle mode? Try again, Main Menu, Exit
+            next_app_state.set(AppState::ResultMenu);
+            next_game_state.set(GameState::None);
+            next_turn_state.set(TurnState::None);
         }
-    }
-    if empty_count == 0 {
-        info!("Level ended | Failure");
-        // In puzzle mode? Try again, Main Menu, Exit
-        next_app_state.set(AppState::ResultMenu);
-        next_game_state.set(GameState::None);
-        next_turn_state.set(TurnState::None);
-    } else if fire_count == 0 {
-        info!("Level ended | Success");
-        next_app_state.set(AppState::LevelMenu);
-        next_game_state.set(GameState::Loaded);
-        next_turn_state.set(TurnState::None);
-        level_index.0 += 1;
+        Outcome::Success => {
+            info!("Level ended | Success");
+            if *mode == GameMode::Rogue {
+                next_app_state.set(AppState::Shop);
+                next_game_state.set(GameState::Shopping);
+            } else {
+                // The next puzzle loads behind the score
+                next_app_state.set(AppState::ResultMenu);
+                next_game_state.set(GameState::Loaded);
+            }
+            next_turn_state.set(TurnState::None);
+            level_index.0 += 1;
+        }
+        Outcome::InProgress => {}
     }
 }
 
-fn put_flames_out(
+/// Stamps out flames under the player and lets them refill from puddles.
+fn stand_on_tile(
+    mut commands: Commands,
     mut tiles: Query<&mut Tile>,
-    pos: Query<&GamePosition, (With<Player>, Changed<GamePosition>)>,
+    player: Query<(Entity, Ref<GamePosition>, &Water), With<Player>>,
     grid: Query<&Grid>,
 ) {
-    if pos.is_empty() {
+    let Ok((player_id, pos, water)) = player.get_single() else {
+        return;
+    };
+    if !pos.is_changed() {
         return;
     }
-    let pos = pos
-        .get_single()
-        .expect("Should only be one player position");
     let grid = grid.get_single().expect("Failed to get grid");
-    let tile_id = grid.get(pos).expect("Failed to get tile id");
+    let mut board = grid.board(|id| tiles.get(id).ok().cloned());
+    if board.step_on(&pos) {
+        commands.spawn(Change {
+            entity: player_id,
+            updated_value: Water {
+                current: water.current.max(water.maxium),
+                maxium: water.maxium,
+            },
+        });
+    }
+    let tile_id = grid.get(&pos).expect("Failed to get tile id");
     let mut tile = tiles.get_mut(tile_id).expect("Failed to get tile");
-    *tile = Tile::Empty;
+    if let Some(after) = board.get(&pos).filter(|after| *after != &*tile) {
+        *tile = after.clone();
+    }
 }
 
 #[derive(Component)]
@@ -304,55 +367,28 @@ fn end_turn_clicked(
 
 fn update_playability(
     player_info: Query<(&GamePosition, &Energy, &Water, &Hand), With<Player>>,
-    mut card_instances: Query<(&ContentID, &mut CardStatus)>,
+    mut card_instances: Query<(&ContentID, Option<&Upgrade>, &mut CardStatus)>,
     card_info: Res<CardInfoMap>,
     tile_grid: Query<&Grid>,
-    blocked_tiles: Query<&BlockedTile>,
+    tiles: Query<&Tile>,
 ) {
     let (position, energy, water, hand) = player_info
         .get_single()
         .expect("Should be exactly 1 player");
     let tile_grid = tile_grid.get_single().expect("Failed to get tile grid");
+    let board = tile_grid.board(|id| tiles.get(id).ok().cloned());
     for card_instance_id in hand.0.iter().flatten() {
-        let (content_id, mut status) = card_instances
+        let (content_id, upgrade, mut status) = card_instances
             .get_mut(*card_instance_id)
             .expect("Failed to get card instance");
         let card_info = card_info
-            .0
-            .get(&*content_id)
+            .effective(content_id, upgrade)
             .expect("Failed to get card info");
-        if card_info.resource_cost.energy > energy.current
-            || card_info.resource_cost.water > water.current
-        {
-            *status = CardStatus::Unplayable;
-            continue;
-        }
-        match &card_info.position_change {
-            MovementInfo {
-                position: TileTarget::FacingDist(dist),
-                rotation: rot,
-            } => {
-                let target_pos = position.rotated(rot).offset((*dist, 0));
-                let tile_id = tile_grid.get(&target_pos);
-                if tile_id.is_none() || blocked_tiles.get(tile_id.unwrap()).is_ok() {
-                    *status = CardStatus::Unplayable;
-                    continue;
-                }
-            }
-            MovementInfo {
-                position: TileTarget::FacingOffsets(offsets),
-                rotation: _,
-            } => {
-                let target_pos =
-                    &TileTarget::FacingOffsets(offsets.clone()).get_positions(&position)[0];
-                let tile_id = tile_grid.get(&target_pos);
-                if tile_id.is_none() || blocked_tiles.get(tile_id.unwrap()).is_ok() {
-                    *status = CardStatus::Unplayable;
-                    continue;
-                }
-            }
-        }
-        *status = CardStatus::Playable;
+        *status = if card_is_playable(&card_info, position, energy, water, &board) {
+            CardStatus::Playable
+        } else {
+            CardStatus::Unplayable
+        };
     }
 }
 
@@ -360,9 +396,16 @@ fn reset_game(
     mut deck_list: ResMut<DeckList>,
     mut level_index: ResMut<LevelIndex>,
     mut map_parameters: ResMut<MapParameters>,
+    mode: Res<GameMode>,
+    profile: Res<Profile>,
+    puzzles: Res<Puzzles>,
 ) {
     *deck_list = DeckList::default();
-    *level_index = LevelIndex::default();
+    // Puzzles pick up where the profile left off
+    *level_index = match *mode {
+        GameMode::Puzzle => LevelIndex(profile.next_puzzle(&puzzles) as i32),
+        GameMode::Rogue => LevelIndex::default(),
+    };
     *map_parameters = MapParameters::default();
 }
 pub struct GamePlugin;
@@ -374,11 +417,29 @@ impl Plugin for GamePlugin {
             .init_resource::<TileSpriteSheet>()
             .init_resource::<IconSpriteSheet>()
             .init_resource::<ItemSpriteSheet>()
+            .init_asset::<CardDefinitions>()
+            .init_asset_loader::<CardDefinitionsLoader>()
+            .init_resource::<CardDefinitionsHandle>()
             .init_resource::<CardInfoMap>()
+            .init_asset::<Level>()
+            .init_asset::<PuzzleIndex>()
+            .init_asset_loader::<PuzzleLoader>()
+            .init_asset_loader::<PuzzleIndexLoader>()
+            .init_resource::<PuzzleSource>()
+            .init_resource::<Puzzles>()
             .init_resource::<DeckList>()
             .init_resource::<MapParameters>()
             .init_resource::<GameMode>()
             .init_resource::<LevelIndex>()
+            .init_resource::<GameRng>()
+            .init_resource::<UndoStack>()
+            .init_resource::<LevelCheckpoint>()
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<ShopOffer>()
+            .init_resource::<ShowForecast>()
+            .init_resource::<LevelStats>()
+            .init_resource::<LastScore>()
+            .insert_resource(Profile::load().unwrap_or_default())
             .add_plugins(ui::GameUIPlugin)
             .add_state::<GameState>()
             .add_state::<TurnState>()
@@ -387,11 +448,7 @@ impl Plugin for GamePlugin {
                     from: GameState::None,
                     to: GameState::Loading,
                 },
-                (
-                    load_card_infos,
-                    spawn_player,
-                    schedule_transition::<NextGameState>,
-                ),
+                (spawn_player, schedule_transition::<NextGameState>),
             )
             .add_systems(
                 OnEnter(GameState::None),
@@ -404,13 +461,30 @@ impl Plugin for GamePlugin {
                 ),
             )
             .add_systems(Update, reset_game.run_if(resource_changed::<GameMode>()))
+            .add_systems(Update, (load_card_infos, collect_puzzles))
             .add_systems(
                 OnExit(GameState::Playing),
-                (despawn_tiles_and_items, despawn_cards),
+                (
+                    despawn_tiles_and_items,
+                    despawn_cards,
+                    clear_undo_stack,
+                    clear_pending_discard,
+                    finish_recording,
+                ),
             )
             .add_systems(
                 OnEnter(GameState::Loaded),
-                (prepare_for_new_level, spawn_cards, spawn_tiles).chain(),
+                (
+                    restore_saved_run,
+                    start_playback,
+                    checkpoint_level_rng,
+                    save_rogue_run,
+                    begin_recording,
+                    prepare_for_new_level,
+                    spawn_cards,
+                    spawn_tiles,
+                )
+                    .chain(),
             )
             .add_systems(
                 Update,
@@ -420,16 +494,38 @@ impl Plugin for GamePlugin {
                 .run_if(in_state(AppState::Game))
                 .run_if(in_state(GameState::Loaded)),
             )
+            // Another puzzle was picked while the current one waited to start
+            .add_systems(
+                OnTransition {
+                    from: GameState::Loaded,
+                    to: GameState::Loading,
+                },
+                (
+                    despawn_tiles_and_items,
+                    despawn_cards,
+                    schedule_transition::<NextGameState>,
+                ),
+            )
             .add_systems(
                 OnTransition {
                     from: GameState::Loaded,
                     to: GameState::Playing,
                 },
-                (shuffle_deck, schedule_transition::<NextTurnState>),
+                (
+                    shuffle_deck,
+                    reset_level_stats,
+                    schedule_transition::<NextTurnState>,
+                ),
             )
             .add_systems(
                 OnEnter(TurnState::Starting),
-                (fill_hand_with_cards, restore_resources).run_if(in_state(GameState::Playing)),
+                (
+                    fill_hand_with_cards,
+                    restore_resources,
+                    clear_undo_stack,
+                    count_turn,
+                )
+                    .run_if(in_state(GameState::Playing)),
             )
             .add_systems(
                 Update,
@@ -454,11 +550,25 @@ impl Plugin for GamePlugin {
                     transition::<TurnState, NextTurnState>,
                 ),
             )
+            .add_systems(
+                OnEnter(TurnState::Ended),
+                (record_end_turn, clear_pending_discard),
+            )
+            .add_systems(
+                Update,
+                (
+                    record_plays.before(apply_card_actions),
+                    record_undos.run_if(in_state(TurnState::WaitingForInput)),
+                    feed_replay_inputs
+                        .run_if(in_state(TurnState::WaitingForInput))
+                        .run_if(resource_exists::<Playback>()),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_systems(
                 OnEnter(TurnState::Ended),
                 (
-                    propagate_flames,
-                    grow_flames,
+                    advance_fire,
                     |mut next_state: ResMut<NextState<TurnState>>| {
                         next_state.set(TurnState::Starting)
                     },
@@ -475,14 +585,50 @@ impl Plugin for GamePlugin {
                     add_item_sprite,
                     apply_item,
                     apply_card,
+                    push_undo_snapshot.before(apply_card_actions),
                     apply_card_actions,
                     check_for_level_end.run_if(not(in_state(TurnState::Animating))),
                     sync_deck,
                     sync_hand,
                     update_tiles,
-                    put_flames_out,
-                    play_clicked_card.run_if(in_state(TurnState::WaitingForInput)),
-                    end_turn_clicked.run_if(in_state(TurnState::WaitingForInput)),
+                    stand_on_tile,
+                    play_clicked_card
+                        .run_if(in_state(TurnState::WaitingForInput))
+                        .run_if(not(resource_exists::<Playback>()))
+                        .run_if(not(resource_exists::<PendingDiscard>())),
+                    discard_clicked_card
+                        .run_if(in_state(TurnState::WaitingForInput))
+                        .run_if(not(resource_exists::<Playback>()))
+                        .run_if(resource_exists::<PendingDiscard>()),
+                    skip_impossible_discard
+                        .run_if(in_state(TurnState::WaitingForInput))
+                        .run_if(resource_exists::<PendingDiscard>()),
+                    end_turn_clicked
+                        .run_if(in_state(TurnState::WaitingForInput))
+                        .run_if(not(resource_exists::<Playback>()))
+                        .run_if(not(resource_exists::<PendingDiscard>())),
+                    undo_last_play.run_if(in_state(TurnState::WaitingForInput)),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (toggle_forecast, update_forecast.after(toggle_forecast))
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    count_plays.before(apply_card_actions),
+                    uncount_undos.before(undo_last_play),
+                    track_peak_fire,
+                    score_level
+                        .after(track_peak_fire)
+                        .before(check_for_level_end)
+                        .run_if(not(in_state(TurnState::Animating))),
+                    update_profile
+                        .after(score_level)
+                        .before(check_for_level_end),
                 )
                     .run_if(in_state(GameState::Playing)),
             );
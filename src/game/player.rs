@@ -64,7 +64,7 @@ impl PlayerBundle {
                 recycled: vec![],
                 discarded: vec![],
             },
-            hand: Hand([None; 5]),
+            hand: Hand([None; HAND_SIZE]),
         }
     }
 }
@@ -0,0 +1,201 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const PROFILE_KEY: &str = "profile.ron";
+
+/// The best a puzzle has been finished.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PuzzleRecord {
+    pub best_score: i32,
+    pub best_stars: u32,
+    pub best_turns: u32,
+}
+
+/// Progress kept between launches.
+#[derive(Clone, Default, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Puzzles open to play, counted from the first. Finishing one opens the next.
+    pub unlocked_puzzles: usize,
+    /// Finished puzzles by asset path.
+    pub puzzles: BTreeMap<String, PuzzleRecord>,
+    /// The deepest level any Rogue run has reached, counting from 1.
+    pub rogue_best_depth: i32,
+}
+
+/// The key a puzzle's record is kept under.
+pub fn puzzle_key(puzzles: &Puzzles, index: usize) -> Option<String> {
+    if puzzles.is_empty() {
+        return None;
+    }
+    let handle = &puzzles.0[index % puzzles.len()];
+    handle.path().map(|path| path.to_string())
+}
+
+impl Profile {
+    pub fn load() -> Option<Self> {
+        let text = read_save(PROFILE_KEY)?;
+        match ron::from_str(&text) {
+            Ok(profile) => Some(profile),
+            Err(error) => {
+                warn!("Ignoring unreadable profile: {}", error);
+                None
+            }
+        }
+    }
+
+    pub fn store(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(text) => write_save(PROFILE_KEY, &text),
+            Err(error) => error!("Failed to serialize the profile: {}", error),
+        }
+    }
+
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index < self.unlocked_puzzles.max(1)
+    }
+
+    pub fn record(&self, puzzles: &Puzzles, index: usize) -> Option<&PuzzleRecord> {
+        self.puzzles.get(&puzzle_key(puzzles, index)?)
+    }
+
+    /// The first unlocked puzzle that hasn't been finished, or the last
+    /// unlocked one once they all have.
+    pub fn next_puzzle(&self, puzzles: &Puzzles) -> usize {
+        let unlocked = self.unlocked_puzzles.max(1).min(puzzles.len().max(1));
+        (0..unlocked)
+            .find(|index| self.record(puzzles, *index).is_none())
+            .unwrap_or(unlocked - 1)
+    }
+
+    /// Keeps the better of the new score and the old record, and opens the
+    /// puzzle after a finished one.
+    fn finish_puzzle(&mut self, key: String, index: usize, score: &Score) {
+        self.unlocked_puzzles = self.unlocked_puzzles.max(index + 2);
+        let record = self.puzzles.entry(key).or_insert(PuzzleRecord {
+            best_score: 0,
+            best_stars: 0,
+            best_turns: u32::MAX,
+        });
+        record.best_score = record.best_score.max(score.points());
+        record.best_stars = record.best_stars.max(score.stars());
+        record.best_turns = record.best_turns.min(score.turns);
+    }
+}
+
+/// Saves the result of the level that just ended. Replays and play-tests
+/// don't count.
+pub fn update_profile(
+    mut profile: ResMut<Profile>,
+    last_score: Res<LastScore>,
+    mode: Res<GameMode>,
+    level_index: Res<LevelIndex>,
+    puzzles: Res<Puzzles>,
+    playback: Option<Res<Playback>>,
+    playtest: Option<Res<PlaytestLevel>>,
+) {
+    let Some(score) = last_score.0.as_ref() else {
+        return;
+    };
+    if !last_score.is_changed() || playback.is_some() || playtest.is_some() {
+        return;
+    }
+    match *mode {
+        GameMode::Puzzle => {
+            if !score.won {
+                return;
+            }
+            let index = level_index.0 as usize % puzzles.len().max(1);
+            let Some(key) = puzzle_key(&puzzles, index) else {
+                return;
+            };
+            profile.finish_puzzle(key, index, score);
+        }
+        GameMode::Rogue => {
+            let depth = level_index.0 + 1;
+            if depth <= profile.rogue_best_depth {
+                return;
+            }
+            profile.rogue_best_depth = depth;
+        }
+    }
+    profile.store();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Puzzles with asset paths for their records to be kept under. Nothing is
+    /// actually loaded.
+    fn puzzles(count: usize) -> Puzzles {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Level>();
+        let server = app.world.resource::<AssetServer>();
+        Puzzles(
+            (0..count)
+                .map(|index| server.load(format!("puzzles/test_{}.puzzle", index)))
+                .collect(),
+        )
+    }
+
+    fn win(turns: u32, cards_played: u32) -> Score {
+        Score {
+            won: true,
+            turns,
+            cards_played,
+            peak_fire: 1,
+            energy_left: 0,
+            water_left: 0,
+            items_collected: 0,
+            par: Some(Par { turns: 2, cards: 4 }),
+        }
+    }
+
+    #[test]
+    fn finishing_a_puzzle_opens_the_next() {
+        let puzzles = puzzles(3);
+        let mut profile = Profile::default();
+        assert!(profile.is_unlocked(0));
+        assert!(!profile.is_unlocked(1));
+        assert_eq!(profile.next_puzzle(&puzzles), 0);
+
+        profile.finish_puzzle(puzzle_key(&puzzles, 0).unwrap(), 0, &win(3, 6));
+        assert!(profile.is_unlocked(1));
+        assert!(!profile.is_unlocked(2));
+        assert_eq!(profile.next_puzzle(&puzzles), 1);
+
+        // Replaying an earlier puzzle doesn't open any more
+        profile.finish_puzzle(puzzle_key(&puzzles, 0).unwrap(), 0, &win(1, 2));
+        assert_eq!(profile.unlocked_puzzles, 2);
+        let record = profile.record(&puzzles, 0).expect("Puzzle 1 was finished");
+        assert_eq!((record.best_stars, record.best_turns), (3, 1));
+        assert_eq!(record.best_score, win(1, 2).points());
+    }
+
+    #[test]
+    fn records_keep_the_best_of_each() {
+        let puzzles = puzzles(1);
+        let key = puzzle_key(&puzzles, 0).unwrap();
+        let mut profile = Profile::default();
+        profile.finish_puzzle(key.clone(), 0, &win(1, 6));
+        profile.finish_puzzle(key, 0, &win(3, 1));
+        let record = profile.record(&puzzles, 0).expect("Puzzle was finished");
+        assert_eq!(record.best_stars, 2);
+        assert_eq!(record.best_turns, 1);
+        assert_eq!(record.best_score, win(1, 6).points().max(win(3, 1).points()));
+    }
+
+    #[test]
+    fn next_puzzle_stays_on_the_last_once_all_are_finished() {
+        let puzzles = puzzles(2);
+        let mut profile = Profile::default();
+        for index in 0..2 {
+            profile.finish_puzzle(puzzle_key(&puzzles, index).unwrap(), index, &win(2, 4));
+        }
+        assert_eq!(profile.unlocked_puzzles, 3);
+        assert_eq!(profile.next_puzzle(&puzzles), 1);
+    }
+}
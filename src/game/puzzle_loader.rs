@@ -0,0 +1,382 @@
+use super::*;
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::utils::BoxedFuture;
+use thiserror::Error;
+
+pub const PUZZLE_INDEX_FILE: &str = "puzzles.index";
+
+#[derive(Debug, Error)]
+pub enum PuzzleError {
+    #[error("Could not read puzzle: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Puzzle is not valid UTF-8")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("Line {line}: unknown key `{key}`")]
+    UnknownKey { line: usize, key: String },
+    #[error("Line {line}: expected a `key: value` pair")]
+    MissingValue { line: usize },
+    #[error("Line {line}: `{value}` is not a valid number")]
+    InvalidNumber { line: usize, value: String },
+    #[error("Line {line}: `card` expects a lowercase letter and a card id")]
+    InvalidCard { line: usize },
+    #[error("Line {line}: `par` expects a number of turns and a number of cards")]
+    InvalidPar { line: usize },
+    #[error("Line {line}, column {column}: unknown tile `{tile}`")]
+    UnknownTile {
+        line: usize,
+        column: usize,
+        tile: char,
+    },
+    #[error("Line {line}: every map row must have the same width")]
+    RaggedMap { line: usize },
+    #[error("Line {line}: the player start is placed more than once")]
+    MultipleStarts { line: usize },
+    #[error("Puzzle has no map")]
+    MissingMap,
+    #[error("Puzzle has an empty deck")]
+    EmptyDeck,
+}
+
+fn parse_number<T: std::str::FromStr>(line: usize, value: &str) -> Result<T, PuzzleError> {
+    value.parse().map_err(|_| PuzzleError::InvalidNumber {
+        line,
+        value: value.to_string(),
+    })
+}
+
+/// Puzzle files are plain text. Header lines are `key: value` pairs followed
+/// by a `map:` line and one line per row of the map, top row first:
+///
+/// ```text
+/// // Comments start with two slashes
+/// name: Collector
+/// deck: 1 1 3
+/// card: a 13
+/// par: 3 5
+/// map:
+/// ...1
+/// ....
+/// .a..
+/// ^...
+/// ```
+///
+/// Map tiles are `.` (empty), `#` (wall), `1`-`3` (fire of that intensity),
+/// `~` (puddle), `%` (fuel), `+` (door), `_` (scorched ground),
+/// `^`/`>`/`v`/`<` (player start and facing), `W` (water item), `E` (energy
+/// item) and any lowercase letter declared with a `card:` line (card item).
+/// The surrounding walls are implicit. The optional `par` line gives the
+/// turns and card plays needed for three stars.
+pub fn parse_puzzle(text: &str) -> Result<Level, PuzzleError> {
+    let mut level = Level::new();
+    let mut cards: HashMap<char, ContentID> = HashMap::new();
+    let mut rows: Vec<(usize, &str)> = Vec::new();
+    let mut in_map = false;
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw_line.trim();
+        if trimmed.starts_with("//") || (trimmed.is_empty() && !in_map) {
+            continue;
+        }
+        if in_map {
+            if trimmed.is_empty() {
+                break;
+            }
+            rows.push((line, trimmed));
+            continue;
+        }
+        let (key, value) = trimmed
+            .split_once(':')
+            .ok_or(PuzzleError::MissingValue { line })?;
+        let value = value.trim();
+        match key.trim() {
+            "name" => level.name = value.to_string(),
+            "deck" => {
+                level.deck_list = value
+                    .split_whitespace()
+                    .map(|id| parse_number(line, id))
+                    .collect::<Result<_, _>>()?;
+            }
+            "card" => {
+                let mut parts = value.split_whitespace();
+                let letter = parts
+                    .next()
+                    .and_then(|letter| letter.chars().next())
+                    .filter(|letter| letter.is_ascii_lowercase())
+                    .ok_or(PuzzleError::InvalidCard { line })?;
+                let id = parts.next().ok_or(PuzzleError::InvalidCard { line })?;
+                cards.insert(letter, ContentID(parse_number(line, id)?));
+            }
+            "par" => {
+                let numbers: Vec<&str> = value.split_whitespace().collect();
+                let [turns, cards] = numbers[..] else {
+                    return Err(PuzzleError::InvalidPar { line });
+                };
+                level.par = Some(Par {
+                    turns: parse_number(line, turns)?,
+                    cards: parse_number(line, cards)?,
+                });
+            }
+            "map" => in_map = true,
+            key => {
+                return Err(PuzzleError::UnknownKey {
+                    line,
+                    key: key.to_string(),
+                })
+            }
+        }
+    }
+    if rows.is_empty() {
+        return Err(PuzzleError::MissingMap);
+    }
+    if level.deck_list.is_empty() {
+        return Err(PuzzleError::EmptyDeck);
+    }
+    let columns = rows[0].1.chars().count();
+    level.map_size = (columns as i32, rows.len() as i32);
+    let mut start_line = None;
+    for (row_index, (line, row)) in rows.iter().enumerate() {
+        if row.chars().count() != columns {
+            return Err(PuzzleError::RaggedMap { line: *line });
+        }
+        let y = (rows.len() - row_index) as i32;
+        for (column, tile) in row.chars().enumerate() {
+            let x = column as i32 + 1;
+            let direction = match tile {
+                '^' => Some(GameDirection::Up),
+                '>' => Some(GameDirection::Right),
+                'v' => Some(GameDirection::Down),
+                '<' => Some(GameDirection::Left),
+                _ => None,
+            };
+            if let Some(d) = direction {
+                if start_line.replace(*line).is_some() {
+                    return Err(PuzzleError::MultipleStarts { line: *line });
+                }
+                level.start = GamePosition { x, y, d };
+                continue;
+            }
+            match tile {
+                '.' => {}
+                '#' => level.walls.push((x, y)),
+                '1' => level.flames.push((x, y, Intensity::Low)),
+                '2' => level.flames.push((x, y, Intensity::Medium)),
+                '3' => level.flames.push((x, y, Intensity::High)),
+                '~' => level.terrain.push((x, y, Tile::Puddle)),
+                '%' => level.terrain.push((x, y, Tile::Fuel)),
+                '+' => level.terrain.push((x, y, Tile::Door)),
+                '_' => level.terrain.push((x, y, Tile::Scorched)),
+                'W' => level.items.push((x, y, Item::Water)),
+                'E' => level.items.push((x, y, Item::Energy)),
+                letter if cards.contains_key(&letter) => {
+                    level.items.push((x, y, Item::Card(cards[&letter].clone())));
+                }
+                tile => {
+                    return Err(PuzzleError::UnknownTile {
+                        line: *line,
+                        column: column + 1,
+                        tile,
+                    })
+                }
+            }
+        }
+    }
+    Ok(level)
+}
+
+/// Writes a level in the format read by `parse_puzzle`.
+pub fn puzzle_text(level: &Level) -> String {
+    // `v` is taken by the player start facing down
+    let mut letters = ('a'..='z').filter(|letter| *letter != 'v');
+    let mut cards: Vec<(char, ContentID)> = Vec::new();
+    for (_, _, item) in level.items.iter() {
+        if let Item::Card(content_id) = item {
+            if !cards.iter().any(|(_, id)| id == content_id) {
+                if let Some(letter) = letters.next() {
+                    cards.push((letter, content_id.clone()));
+                }
+            }
+        }
+    }
+    let mut text = format!("name: {}\n", level.name);
+    let deck: Vec<String> = level.deck_list.iter().map(|id| id.to_string()).collect();
+    text.push_str(&format!("deck: {}\n", deck.join(" ")));
+    for (letter, content_id) in cards.iter() {
+        text.push_str(&format!("card: {} {}\n", letter, content_id.0));
+    }
+    if let Some(par) = &level.par {
+        text.push_str(&format!("par: {} {}\n", par.turns, par.cards));
+    }
+    text.push_str("map:\n");
+    let (columns, rows) = level.map_size;
+    for y in (1..=rows).rev() {
+        for x in 1..=columns {
+            let item = level
+                .items
+                .iter()
+                .find(|(item_x, item_y, _)| *item_x == x && *item_y == y);
+            let flame = level
+                .flames
+                .iter()
+                .find(|(flame_x, flame_y, _)| *flame_x == x && *flame_y == y);
+            let terrain = level
+                .terrain
+                .iter()
+                .find(|(tile_x, tile_y, _)| *tile_x == x && *tile_y == y);
+            let tile = if level.start.x == x && level.start.y == y {
+                match level.start.d {
+                    GameDirection::Up => '^',
+                    GameDirection::Right => '>',
+                    GameDirection::Down => 'v',
+                    GameDirection::Left => '<',
+                }
+            } else if level.walls.contains(&(x, y)) {
+                '#'
+            } else if let Some((_, _, intensity)) = flame {
+                match intensity {
+                    Intensity::Low => '1',
+                    Intensity::Medium => '2',
+                    Intensity::High => '3',
+                }
+            } else if let Some((_, _, tile)) = terrain {
+                match tile {
+                    Tile::Puddle => '~',
+                    Tile::Fuel => '%',
+                    Tile::Door => '+',
+                    Tile::Scorched => '_',
+                    _ => '.',
+                }
+            } else {
+                match item {
+                    Some((_, _, Item::Water)) => 'W',
+                    Some((_, _, Item::Energy)) => 'E',
+                    Some((_, _, Item::Card(content_id))) => cards
+                        .iter()
+                        .find(|(_, id)| id == content_id)
+                        .map(|(letter, _)| *letter)
+                        .unwrap_or('.'),
+                    None => '.',
+                }
+            };
+            text.push(tile);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[derive(Default)]
+pub struct PuzzleLoader;
+
+impl AssetLoader for PuzzleLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = PuzzleError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            parse_puzzle(std::str::from_utf8(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzle"]
+    }
+}
+
+/// A list of puzzle file names, one per line, relative to the index file.
+#[derive(Asset, TypePath)]
+pub struct PuzzleIndex(pub Vec<Handle<Level>>);
+
+#[derive(Default)]
+pub struct PuzzleIndexLoader;
+
+impl AssetLoader for PuzzleIndexLoader {
+    type Asset = PuzzleIndex;
+    type Settings = ();
+    type Error = PuzzleError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let handles = std::str::from_utf8(&bytes)?
+                .lines()
+                .map(str::trim)
+                .filter(|file_name| !file_name.is_empty())
+                .map(|file_name| load_context.load(format!("{}/{}", PUZZLE_FOLDER, file_name)))
+                .collect();
+            Ok(PuzzleIndex(handles))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["index"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_walls_flames_items_and_start() {
+        let level = parse_puzzle(
+            "name: Walled\n\
+             deck: 1 2\n\
+             card: a 13\n\
+             map:\n\
+             #.3\n\
+             Wa#\n\
+             >.E\n",
+        )
+        .expect("Puzzle should parse");
+        assert_eq!(level.name, "Walled");
+        assert_eq!(level.deck_list, vec![1, 2]);
+        assert_eq!(level.map_size, (3, 3));
+        assert_eq!(level.walls, vec![(1, 3), (3, 2)]);
+        assert_eq!(level.flames.len(), 1);
+        assert!(matches!(level.flames[0], (3, 3, Intensity::High)));
+        assert!(matches!(level.items[0], (1, 2, Item::Water)));
+        assert!(matches!(&level.items[1], (2, 2, Item::Card(ContentID(13)))));
+        assert!(matches!(level.items[2], (3, 1, Item::Energy)));
+        assert_eq!((level.start.x, level.start.y), (1, 1));
+        assert!(matches!(level.start.d, GameDirection::Right));
+    }
+
+    #[test]
+    fn walls_survive_writing_and_reading_back() {
+        let text = "name: Detour\ndeck: 17 1\nmap:\n..1\n.#.\n#..\n^..\n";
+        let level = parse_puzzle(text).expect("Puzzle should parse");
+        let reread = parse_puzzle(&puzzle_text(&level)).expect("Written puzzle should parse");
+        assert_eq!(reread.walls, level.walls);
+        assert_eq!(reread.walls, vec![(2, 3), (1, 2)]);
+    }
+
+    #[test]
+    fn rejects_unknown_tiles_and_ragged_rows() {
+        assert!(matches!(
+            parse_puzzle("deck: 1\nmap:\n.?\n^.\n"),
+            Err(PuzzleError::UnknownTile {
+                line: 3,
+                column: 2,
+                tile: '?'
+            })
+        ));
+        assert!(matches!(
+            parse_puzzle("deck: 1\nmap:\n..\n^\n"),
+            Err(PuzzleError::RaggedMap { line: 4 })
+        ));
+    }
+}
@@ -1,105 +1,122 @@
 use super::*;
+use bevy::asset::LoadedFolder;
 
-#[derive(Clone)]
+#[derive(Asset, TypePath, Clone)]
 pub struct Level {
-    pub flames: Vec<(i32, i32)>,
+    pub name: String,
+    pub flames: Vec<(i32, i32, Intensity)>,
+    /// Interior walls; the border walls are implicit.
+    pub walls: Vec<(i32, i32)>,
+    /// Puddles, fuel, doors and scorched ground.
+    pub terrain: Vec<(i32, i32, Tile)>,
     pub items: Vec<(i32, i32, Item)>,
     pub map_size: (i32, i32),
+    pub start: GamePosition,
     pub deck_list: Vec<usize>,
+    pub par: Option<Par>,
 }
 
 impl Level {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
+            name: String::new(),
             flames: Vec::new(),
+            walls: Vec::new(),
+            terrain: Vec::new(),
             items: Vec::new(),
             map_size: (1, 1),
+            start: GamePosition {
+                x: 1,
+                y: 1,
+                d: GameDirection::Up,
+            },
             deck_list: Vec::new(),
+            par: None,
         }
     }
-    fn with_flames(&self, flames: Vec<(i32, i32)>) -> Self {
-        let mut new_flames = self.flames.clone();
-        new_flames.extend(flames);
-        Self {
-            flames: new_flames,
-            ..self.clone()
+}
+
+/// A level started from the editor instead of the puzzle list.
+#[derive(Resource)]
+pub struct PlaytestLevel(pub Level);
+
+pub const PUZZLE_FOLDER: &str = "puzzles";
+
+/// Where the puzzle files are discovered from. The web build cannot list
+/// directories over HTTP, so it reads a generated index of the folder instead.
+#[derive(Resource)]
+pub enum PuzzleSource {
+    Folder(Handle<LoadedFolder>),
+    Index(Handle<PuzzleIndex>),
+}
+
+impl FromWorld for PuzzleSource {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world
+            .get_resource::<AssetServer>()
+            .expect("Failed get the `AssetServer` resource from the `World`");
+        if cfg!(target_arch = "wasm32") {
+            Self::Index(asset_server.load(format!("{}/{}", PUZZLE_FOLDER, PUZZLE_INDEX_FILE)))
+        } else {
+            Self::Folder(asset_server.load_folder(PUZZLE_FOLDER))
         }
     }
-    fn with_items(&self, items: Vec<(i32, i32, Item)>) -> Self {
-        let mut new_items = self.items.clone();
-        new_items.extend(items);
-        Self {
-            items: new_items,
-            ..self.clone()
-        }
+}
+
+/// The puzzles that loaded successfully, ordered by file name.
+#[derive(Default, Resource)]
+pub struct Puzzles(pub Vec<Handle<Level>>);
+
+impl Puzzles {
+    pub fn len(&self) -> usize {
+        self.0.len()
     }
-    fn with_size(&self , size: (i32, i32)) -> Self {
-        Self {
-            map_size: size,
-            ..self.clone()
-        }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
-    fn with_deck(&self, deck: Vec<usize>) -> Self {
-        Self {
-            deck_list: deck,
-            ..self.clone()
+
+    pub fn get<'a>(&self, index: usize, levels: &'a Assets<Level>) -> Option<&'a Level> {
+        if self.0.is_empty() {
+            return None;
         }
+        levels.get(&self.0[index % self.0.len()])
     }
 }
 
-pub const NUM_PUZZLES: usize = 7;
-
-pub fn get_puzzle(index: usize) -> Level {
-    match index {
-        0 => {
-            Level::new()
-                .with_size((1, 3))
-                .with_flames(vec![(1, 3)])
-                .with_deck(vec![1,])
-        },
-        1 => {
-            Level::new()
-                .with_size((2, 2))
-                .with_flames(vec![(2, 2)])
-                .with_deck(vec![2, 4])
-        },
-        2 => {
-            Level::new()
-                .with_size((3, 3))
-                .with_flames(vec![(3, 3)])
-                .with_deck(vec![1, 1, 3])
-        },
-        3 => {
-            Level::new()
-                .with_size((1, 3))
-                .with_flames(vec![(1, 3)])
-                .with_items(vec![(1, 2, Item::Water)])
-                .with_deck(vec![1, 5])
-        },
-        4 => {
-            Level::new()
-                .with_size((4, 4))
-                .with_flames(vec![(4, 4)])
-                .with_items(vec![(2, 2, Item::Card(ContentID(13)))])
-                .with_deck(vec![1, 1, 3])
-        },
-        5 => {
-            Level::new()
-                .with_size((4, 4))
-                .with_flames(vec![(4, 4)])
-                .with_items(vec![(1, 2, Item::Card(ContentID(20)))])
-                .with_items(vec![(2, 1, Item::Water)])
-                .with_items(vec![(2, 2, Item::Card(ContentID(15)))])
-                .with_deck(vec![1, ])
-        },
-        6 => {
-            Level::new()
-                .with_size((5, 5))
-                .with_flames(vec![(4, 4)])
-                .with_items(vec![(4, 1, Item::Card(ContentID(8)))])
-                .with_deck(vec![1, 3, 4])
-        },
-
-        _ => Level::new()
+pub fn collect_puzzles(
+    mut level_events: EventReader<AssetEvent<Level>>,
+    mut folder_events: EventReader<AssetEvent<LoadedFolder>>,
+    mut index_events: EventReader<AssetEvent<PuzzleIndex>>,
+    source: Res<PuzzleSource>,
+    folders: Res<Assets<LoadedFolder>>,
+    indices: Res<Assets<PuzzleIndex>>,
+    levels: Res<Assets<Level>>,
+    mut puzzles: ResMut<Puzzles>,
+) {
+    let event_count =
+        level_events.read().count() + folder_events.read().count() + index_events.read().count();
+    if event_count == 0 {
+        return;
     }
-}
\ No newline at end of file
+    let mut handles: Vec<Handle<Level>> = match &*source {
+        PuzzleSource::Folder(handle) => folders
+            .get(handle)
+            .map(|folder| {
+                folder
+                    .handles
+                    .iter()
+                    .filter(|handle| handle.type_id() == std::any::TypeId::of::<Level>())
+                    .map(|handle| handle.clone().typed::<Level>())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        PuzzleSource::Index(handle) => indices
+            .get(handle)
+            .map(|index| index.0.clone())
+            .unwrap_or_default(),
+    };
+    handles.retain(|handle| levels.contains(handle));
+    handles.sort_by_key(|handle| handle.path().map(|path| path.to_string()));
+    puzzles.0 = handles;
+}
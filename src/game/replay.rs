@@ -0,0 +1,228 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+pub const REPLAY_KEY: &str = "last_replay.ron";
+
+/// Seconds between inputs during playback, so each play can be followed.
+const PLAYBACK_DELAY: f32 = 0.6;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ReplayInput {
+    /// The card in this hand slot was played.
+    Play(usize),
+    /// The card in this hand slot was chosen to be discarded.
+    Discard(usize),
+    EndTurn,
+    Undo,
+}
+
+/// A level as it was set up, plus every input made while playing it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub rng_state: u64,
+    pub mode: GameMode,
+    pub level_index: i32,
+    pub deck_list: Vec<DeckCard>,
+    pub energy: Energy,
+    pub water: Water,
+    pub inputs: Vec<ReplayInput>,
+}
+
+impl Replay {
+    pub fn load() -> Option<Self> {
+        let text = read_save(REPLAY_KEY)?;
+        match ron::from_str(&text) {
+            Ok(replay) => Some(replay),
+            Err(error) => {
+                warn!("Ignoring unreadable replay: {}", error);
+                None
+            }
+        }
+    }
+
+    pub fn exists() -> bool {
+        Self::load().is_some()
+    }
+
+    pub fn store(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(text) => write_save(REPLAY_KEY, &text),
+            Err(error) => error!("Failed to serialize the replay: {}", error),
+        }
+    }
+}
+
+/// The replay of the level being played.
+#[derive(Default, Resource)]
+pub struct ReplayRecorder(pub Option<Replay>);
+
+/// Present while a replay is being played back instead of taking input.
+#[derive(Resource)]
+pub struct Playback {
+    pub replay: Replay,
+    pub next_input: usize,
+    pub started: bool,
+    pub timer: Timer,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_input: 0,
+            started: false,
+            timer: Timer::from_seconds(PLAYBACK_DELAY, TimerMode::Repeating),
+        }
+    }
+}
+
+pub fn start_playback(
+    playback: Option<ResMut<Playback>>,
+    mut rng: ResMut<GameRng>,
+    mut checkpoint: ResMut<LevelCheckpoint>,
+    mut level_index: ResMut<LevelIndex>,
+    mut deck_list: ResMut<DeckList>,
+    mut player: Query<(&mut Energy, &mut Water), With<Player>>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if playback.started {
+        return;
+    }
+    playback.started = true;
+    let replay = playback.replay.clone();
+    info!("Playing back level {} with seed {}", replay.level_index, replay.seed);
+    *rng = GameRng::new(replay.seed);
+    checkpoint.pending = Some(replay.rng_state);
+    level_index.0 = replay.level_index;
+    deck_list.0 = replay.deck_list;
+    let (mut energy, mut water) = player.get_single_mut().expect("Should be exactly 1 player");
+    *energy = replay.energy;
+    *water = replay.water;
+}
+
+pub fn begin_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    mode: Res<GameMode>,
+    rng: Res<GameRng>,
+    checkpoint: Res<LevelCheckpoint>,
+    level_index: Res<LevelIndex>,
+    deck_list: Res<DeckList>,
+    player: Query<(&Energy, &Water), With<Player>>,
+    playback: Option<Res<Playback>>,
+    playtest: Option<Res<PlaytestLevel>>,
+) {
+    // Play-tests can't be replayed once the editor moves on
+    if playback.is_some() || playtest.is_some() {
+        recorder.0 = None;
+        return;
+    }
+    let (energy, water) = player.get_single().expect("Should be exactly 1 player");
+    recorder.0 = Some(Replay {
+        seed: rng.seed,
+        rng_state: checkpoint.state,
+        mode: *mode,
+        level_index: level_index.0,
+        deck_list: deck_list.0.clone(),
+        energy: energy.clone(),
+        water: water.clone(),
+        inputs: Vec::new(),
+    });
+}
+
+pub fn record_plays(
+    mut recorder: ResMut<ReplayRecorder>,
+    actions: Query<&CardActionType>,
+    hand: Query<&Hand, With<Player>>,
+) {
+    let Some(replay) = recorder.0.as_mut() else {
+        return;
+    };
+    let hand = hand.get_single().expect("Should be exactly 1 hand");
+    let slot_of = |card: Entity| hand.0.iter().position(|slot| *slot == Some(card));
+    for action in actions.iter() {
+        match action {
+            CardActionType::Play(play) => {
+                if let Some(slot) = slot_of(play.card) {
+                    replay.inputs.push(ReplayInput::Play(slot));
+                }
+            }
+            // Only discards from the hand are choices; exhausted cards have already left it
+            CardActionType::Discard(discard) => {
+                if let Some(slot) = slot_of(discard.card) {
+                    replay.inputs.push(ReplayInput::Discard(slot));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn record_undos(mut recorder: ResMut<ReplayRecorder>, mut events: EventReader<UndoClicked>) {
+    let count = events.read().count();
+    if let Some(replay) = recorder.0.as_mut() {
+        replay
+            .inputs
+            .extend(std::iter::repeat(ReplayInput::Undo).take(count));
+    }
+}
+
+pub fn record_end_turn(mut recorder: ResMut<ReplayRecorder>) {
+    if let Some(replay) = recorder.0.as_mut() {
+        replay.inputs.push(ReplayInput::EndTurn);
+    }
+}
+
+pub fn finish_recording(mut commands: Commands, mut recorder: ResMut<ReplayRecorder>) {
+    if let Some(replay) = recorder.0.take() {
+        replay.store();
+    }
+    commands.remove_resource::<Playback>();
+}
+
+pub fn feed_replay_inputs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut playback: ResMut<Playback>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    mut undo_clicked: EventWriter<UndoClicked>,
+    player: Query<(Entity, &Hand), With<Player>>,
+) {
+    if !playback.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(input) = playback.replay.inputs.get(playback.next_input).cloned() else {
+        return;
+    };
+    playback.next_input += 1;
+    match input {
+        ReplayInput::Play(slot) => {
+            let (player_id, hand) = player.get_single().expect("Should be exactly 1 player");
+            if let Some(card) = hand.0.get(slot).copied().flatten() {
+                commands.spawn(CardActionType::Play(Play {
+                    card,
+                    deck: player_id,
+                    hand: player_id,
+                }));
+            } else {
+                warn!("Replay played an empty hand slot {}", slot);
+            }
+        }
+        ReplayInput::Discard(slot) => {
+            let (player_id, hand) = player.get_single().expect("Should be exactly 1 player");
+            if let Some(card) = hand.0.get(slot).copied().flatten() {
+                commands.spawn(CardActionType::Discard(Discard {
+                    card,
+                    deck: player_id,
+                    hand: player_id,
+                }));
+            } else {
+                warn!("Replay discarded an empty hand slot {}", slot);
+            }
+        }
+        ReplayInput::EndTurn => next_turn_state.set(TurnState::Ended),
+        ReplayInput::Undo => undo_clicked.send(UndoClicked),
+    }
+}
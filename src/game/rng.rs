@@ -0,0 +1,85 @@
+use super::*;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// The single source of randomness for a run. Every random decision (deck
+/// shuffles, flame and item spawns) draws from it, so a run can be replayed
+/// exactly from its seed.
+#[derive(Resource, Clone)]
+pub struct GameRng {
+    pub seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Restarts the generator from a fresh seed drawn from it and returns that
+    /// seed. Saving it lets a run be resumed exactly where it left off.
+    pub fn checkpoint(&mut self) -> u64 {
+        let state = self.rng.next_u64();
+        self.rng = StdRng::seed_from_u64(state);
+        state
+    }
+
+    /// Picks a run back up from a seed returned by `checkpoint`.
+    pub fn resume(seed: u64, state: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(state),
+        }
+    }
+
+    /// A seed short enough to read off the screen and type back in.
+    pub fn random_seed() -> u64 {
+        rand::random::<u32>() as u64
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(Self::random_seed())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// The generator state the current level started from. Saves and replays
+/// store it; restoring one sets `pending` so the level starts from the stored
+/// state instead of a fresh checkpoint.
+#[derive(Default, Resource)]
+pub struct LevelCheckpoint {
+    pub state: u64,
+    pub pending: Option<u64>,
+}
+
+pub fn checkpoint_level_rng(mut rng: ResMut<GameRng>, mut checkpoint: ResMut<LevelCheckpoint>) {
+    checkpoint.state = match checkpoint.pending.take() {
+        Some(state) => {
+            *rng = GameRng::resume(rng.seed, state);
+            state
+        }
+        None => rng.checkpoint(),
+    };
+}
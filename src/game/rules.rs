@@ -0,0 +1,895 @@
+use super::*;
+
+// The game rules without any Bevy state. The ECS systems build a `Board` from
+// the tile entities and ask these functions what should happen, while the
+// `Simulation` runs whole levels headlessly for solvers, replays and bots.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    InProgress,
+    Success,
+    Failure,
+}
+
+/// How much wind changes the chance of fire spreading with or against it.
+const WIND_BIAS: f32 = 0.5;
+
+/// Rolls a chance, without touching the RNG when the outcome is certain so
+/// deterministic fire leaves the random sequence alone.
+fn roll<R: Rng + ?Sized>(rng: &mut R, chance: f32) -> bool {
+    if chance >= 1.0 {
+        true
+    } else if chance <= 0.0 {
+        false
+    } else {
+        rng.gen_bool(f64::from(chance))
+    }
+}
+
+/// Success once every flame is out, failure once no open ground is left.
+pub fn outcome<'a>(tiles: impl Iterator<Item = &'a Tile>) -> Outcome {
+    let mut fire_count = 0;
+    let mut empty_count = 0;
+    for tile in tiles {
+        match tile {
+            Tile::Fire(_) => fire_count += 1,
+            Tile::Empty | Tile::Puddle | Tile::Fuel | Tile::Scorched => empty_count += 1,
+            Tile::Wall | Tile::Door => {}
+        }
+    }
+    if empty_count == 0 {
+        Outcome::Failure
+    } else if fire_count == 0 {
+        Outcome::Success
+    } else {
+        Outcome::InProgress
+    }
+}
+
+/// The tile after one end of turn of growth.
+pub fn grown(tile: &Tile) -> Tile {
+    match tile {
+        Tile::Fire(Intensity::Low) => Tile::Fire(Intensity::Medium),
+        Tile::Fire(Intensity::Medium) => Tile::Fire(Intensity::High),
+        tile => tile.clone(),
+    }
+}
+
+/// The tile once the player stands on it, or `None` if that has no effect.
+/// Flames are stamped out and puddles are used up.
+pub fn trodden(tile: &Tile) -> Option<Tile> {
+    match tile {
+        Tile::Fire(_) | Tile::Puddle => Some(Tile::Empty),
+        _ => None,
+    }
+}
+
+/// The tile once fire spreads to it, or `None` if it can't catch.
+pub fn ignited(tile: &Tile) -> Option<Tile> {
+    match tile {
+        Tile::Empty => Some(Tile::Fire(Intensity::Low)),
+        Tile::Fuel => Some(Tile::Fire(Intensity::High)),
+        Tile::Puddle => Some(Tile::Empty),
+        _ => None,
+    }
+}
+
+/// The tile after `amount` water damage, or `None` if water has no effect on
+/// it. Each point knocks a fire down one intensity until it goes out.
+pub fn doused(tile: &Tile, amount: u32) -> Option<Tile> {
+    match tile {
+        Tile::Fire(intensity) => Some(match (intensity.clone() as u32).saturating_sub(amount) {
+            0 => Tile::Empty,
+            1 => Tile::Fire(Intensity::Low),
+            2 => Tile::Fire(Intensity::Medium),
+            _ => Tile::Fire(Intensity::High),
+        }),
+        _ => None,
+    }
+}
+
+/// Where the player ends up after a move.
+pub fn movement_target(position: &GamePosition, target: &TileTarget) -> GamePosition {
+    match target {
+        TileTarget::FacingDist(dist) => position.offset((*dist, 0)),
+        TileTarget::FacingOffsets(_) => target.get_positions(position)[0].clone(),
+    }
+}
+
+/// The tiles the player passes through on the way to a move's target, one
+/// list per route. The move is blocked unless some route is clear of walls, so
+/// long moves can't jump a wall and diagonal moves can't cut through a corner.
+pub fn movement_routes(position: &GamePosition, target: &TileTarget) -> Vec<Vec<GamePosition>> {
+    let steps = |from: &GamePosition, by: (i32, i32)| -> Vec<GamePosition> {
+        let count = by.0.abs().max(by.1.abs());
+        (1..=count)
+            .map(|step| from.offset((by.0.signum() * step, by.1.signum() * step)))
+            .collect()
+    };
+    match target {
+        TileTarget::FacingDist(dist) => vec![steps(position, (*dist, 0))],
+        TileTarget::FacingOffsets(offsets) => {
+            let Some(offset) = offsets.first() else {
+                return vec![Vec::new()];
+            };
+            let facing_first = position.offset((offset.facing, 0));
+            let tangent_first = position.offset((0, offset.tangent));
+            vec![
+                [steps(position, (offset.facing, 0)), steps(&facing_first, (0, offset.tangent))]
+                    .concat(),
+                [steps(position, (0, offset.tangent)), steps(&tangent_first, (offset.facing, 0))]
+                    .concat(),
+            ]
+        }
+    }
+}
+
+/// The tiles hit by water sprayed from this position.
+pub fn damage_targets(position: &GamePosition, damage: &DamageInfo) -> Vec<GamePosition> {
+    if damage.amount == 0 {
+        return Vec::new();
+    }
+    damage.damage_target.get_positions(position)
+}
+
+/// One board effect of a card once it has been worked out, for animating.
+#[derive(Clone, Debug)]
+pub enum EffectStep {
+    Moved(GamePosition),
+    Rotated(GamePosition),
+    Sprayed {
+        targets: Vec<GamePosition>,
+        /// Fires put out completely; the rest only burn lower.
+        extinguished: Vec<GamePosition>,
+    },
+    WallsPlaced(Vec<GamePosition>),
+    DoorsOpened(Vec<GamePosition>),
+}
+
+/// A card's effects worked through in order on a copy of the board.
+#[derive(Clone)]
+pub struct Resolution {
+    pub steps: Vec<EffectStep>,
+    pub position: GamePosition,
+    pub board: Board,
+    pub gained: ResourceInfo,
+}
+
+/// Runs the board side of a card's effects from the player's position, or
+/// returns `None` if one of its moves is blocked. Hand and deck effects are
+/// left to the caller.
+pub fn resolve_effects(
+    effects: &[CardEffect],
+    position: &GamePosition,
+    board: &Board,
+) -> Option<Resolution> {
+    let mut resolution = Resolution {
+        steps: Vec::new(),
+        position: position.clone(),
+        board: board.clone(),
+        gained: ResourceInfo {
+            energy: 0,
+            water: 0,
+        },
+    };
+    for effect in effects.iter() {
+        let position = resolution.position.clone();
+        let board = &mut resolution.board;
+        match effect {
+            CardEffect::Move(target) => {
+                let to = movement_target(&position, target);
+                let clear = board.is_open(&to)
+                    && movement_routes(&position, target)
+                        .iter()
+                        .any(|route| route.iter().all(|step| board.is_open(step)));
+                if !clear {
+                    return None;
+                }
+                resolution.position = to.clone();
+                resolution.steps.push(EffectStep::Moved(to));
+            }
+            CardEffect::Rotate(rotation) => {
+                resolution.position = position.rotated(rotation);
+                resolution
+                    .steps
+                    .push(EffectStep::Rotated(resolution.position.clone()));
+            }
+            CardEffect::MoveUntilBlocked => {
+                let mut to = position;
+                while board.is_open(&to.offset((1, 0))) {
+                    to = to.offset((1, 0));
+                }
+                resolution.position = to.clone();
+                resolution.steps.push(EffectStep::Moved(to));
+            }
+            CardEffect::Extinguish(damage) => {
+                let targets = damage_targets(&position, damage);
+                let mut extinguished_positions = Vec::new();
+                for target in targets.iter() {
+                    if let Some(tile) = board
+                        .get(target)
+                        .and_then(|tile| doused(tile, damage.amount))
+                    {
+                        if tile == Tile::Empty {
+                            extinguished_positions.push(target.clone());
+                        }
+                        board.set(target, tile);
+                    }
+                }
+                resolution.steps.push(EffectStep::Sprayed {
+                    targets,
+                    extinguished: extinguished_positions,
+                });
+            }
+            CardEffect::Gain(gain) => {
+                resolution.gained.energy += gain.energy;
+                resolution.gained.water += gain.water;
+            }
+            CardEffect::PlaceWall(target) => {
+                let walls: Vec<GamePosition> = target
+                    .get_positions(&position)
+                    .into_iter()
+                    .filter(|wall| {
+                        board.get(wall) == Some(&Tile::Empty)
+                            && (wall.x, wall.y) != (position.x, position.y)
+                    })
+                    .collect();
+                for wall in walls.iter() {
+                    board.set(wall, Tile::Wall);
+                }
+                resolution.steps.push(EffectStep::WallsPlaced(walls));
+            }
+            CardEffect::OpenDoor(target) => {
+                let doors: Vec<GamePosition> = target
+                    .get_positions(&position)
+                    .into_iter()
+                    .filter(|door| board.get(door) == Some(&Tile::Door))
+                    .collect();
+                for door in doors.iter() {
+                    board.set(door, Tile::Empty);
+                }
+                resolution.steps.push(EffectStep::DoorsOpened(doors));
+            }
+            CardEffect::Draw(_)
+            | CardEffect::Discard(_)
+            | CardEffect::RecycleHand
+            | CardEffect::Exhaust => {}
+        }
+    }
+    Some(resolution)
+}
+
+/// Energy and water go back to half full at the start of every turn.
+pub fn restore_for_turn(energy: &mut Energy, water: &mut Water) {
+    energy.current = energy.maxium / 2;
+    water.current = water.maxium / 2;
+}
+
+pub fn can_afford(card_info: &CardInfo, energy: &Energy, water: &Water) -> bool {
+    card_info.resource_cost.energy <= energy.current && card_info.resource_cost.water <= water.current
+}
+
+pub fn card_is_playable(
+    card_info: &CardInfo,
+    position: &GamePosition,
+    energy: &Energy,
+    water: &Water,
+    board: &Board,
+) -> bool {
+    can_afford(card_info, energy, water)
+        && resolve_effects(&card_info.effects, position, board).is_some()
+}
+
+#[derive(Clone)]
+pub struct Board {
+    /// Tiles indexed by `[y][x]`, including the surrounding walls.
+    pub tiles: Vec<Vec<Tile>>,
+    /// How many turns each High fire has been High, by `(x, y)`.
+    pub high_turns: HashMap<(i32, i32), u32>,
+}
+
+impl Board {
+    pub fn new(columns: i32, rows: i32) -> Self {
+        let tiles = (0..=rows + 1)
+            .map(|y| {
+                (0..=columns + 1)
+                    .map(|x| {
+                        if x == 0 || y == 0 || x == columns + 1 || y == rows + 1 {
+                            Tile::Wall
+                        } else {
+                            Tile::Empty
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            tiles,
+            high_turns: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, pos: &GamePosition) -> Option<&Tile> {
+        if pos.x < 0 || pos.y < 0 {
+            return None;
+        }
+        self.tiles
+            .get(pos.y as usize)
+            .and_then(|row| row.get(pos.x as usize))
+    }
+
+    pub fn set(&mut self, pos: &GamePosition, tile: Tile) {
+        if self.get(pos).is_some() {
+            self.tiles[pos.y as usize][pos.x as usize] = tile;
+        }
+    }
+
+    /// Stamps out the flame or uses up the puddle under the player. Returns
+    /// whether a puddle refilled their water.
+    pub fn step_on(&mut self, pos: &GamePosition) -> bool {
+        let refills = self.get(pos) == Some(&Tile::Puddle);
+        if let Some(tile) = self.get(pos).and_then(trodden) {
+            self.set(pos, tile);
+        }
+        refills
+    }
+
+    /// Whether the player may stand on the tile.
+    pub fn is_open(&self, pos: &GamePosition) -> bool {
+        matches!(self.get(pos), Some(tile) if !tile.is_blocking())
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = GamePosition> + '_ {
+        self.tiles.iter().enumerate().flat_map(|(y, row)| {
+            (0..row.len()).map(move |x| GamePosition {
+                x: x as i32,
+                y: y as i32,
+                ..default()
+            })
+        })
+    }
+
+    pub fn neighbors(&self, pos: &GamePosition, diagonal: bool) -> Vec<GamePosition> {
+        let orthogonal = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let diagonals = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+        let offsets = if diagonal {
+            [orthogonal, diagonals].concat()
+        } else {
+            orthogonal.to_vec()
+        };
+        offsets
+            .iter()
+            .map(|(x, y)| GamePosition {
+                x: pos.x + x,
+                y: pos.y + y,
+                ..pos.clone()
+            })
+            .filter(|neighbor| self.get(neighbor).is_some())
+            .collect()
+    }
+
+    /// The chance each empty tile catches fire at the end of the turn, for
+    /// every tile with a chance at all.
+    pub fn spread_chances(&self, model: &FireModel) -> Vec<(GamePosition, f32)> {
+        let wind = model.wind.as_ref().map(|d| {
+            let ahead = GamePosition {
+                x: 0,
+                y: 0,
+                d: d.clone(),
+            }
+            .offset((1, 0));
+            (ahead.x, ahead.y)
+        });
+        // The chance a tile stays clear of every fire next to it
+        let mut spared: Vec<(GamePosition, f32)> = Vec::new();
+        for pos in self.positions() {
+            let Some(Tile::Fire(intensity)) = self.get(&pos) else {
+                continue;
+            };
+            let base = model.spread_chance[intensity.clone() as usize - 1];
+            for neighbor in self.neighbors(&pos, model.diagonal) {
+                if self.get(&neighbor).and_then(ignited).is_none() {
+                    continue;
+                }
+                let chance = match wind {
+                    Some((wind_x, wind_y)) => {
+                        let along = (neighbor.x - pos.x) * wind_x + (neighbor.y - pos.y) * wind_y;
+                        base * (1.0 + WIND_BIAS * along.signum() as f32)
+                    }
+                    None => base,
+                }
+                .clamp(0.0, 1.0);
+                if chance <= 0.0 {
+                    continue;
+                }
+                match spared
+                    .iter_mut()
+                    .find(|(target, _)| target.x == neighbor.x && target.y == neighbor.y)
+                {
+                    Some((_, stays_clear)) => *stays_clear *= 1.0 - chance,
+                    None => spared.push((neighbor, 1.0 - chance)),
+                }
+            }
+        }
+        spared
+            .into_iter()
+            .map(|(target, stays_clear)| (target, 1.0 - stays_clear))
+            .collect()
+    }
+
+    /// The empty tiles that catch fire at the end of the turn.
+    pub fn spread_targets<R: Rng + ?Sized>(
+        &self,
+        model: &FireModel,
+        rng: &mut R,
+    ) -> Vec<GamePosition> {
+        self.spread_chances(model)
+            .into_iter()
+            .filter(|(_, chance)| roll(rng, *chance))
+            .map(|(target, _)| target)
+            .collect()
+    }
+
+    pub fn spread_flames(&mut self, targets: &[GamePosition]) {
+        for pos in targets.iter() {
+            if let Some(tile) = self.get(pos).and_then(ignited) {
+                self.set(pos, tile);
+            }
+        }
+    }
+
+    pub fn grow_flames(&mut self) {
+        for row in self.tiles.iter_mut() {
+            for tile in row.iter_mut() {
+                *tile = grown(tile);
+            }
+        }
+    }
+
+    /// Counts another turn for every High fire and burns out the ones that
+    /// have been High for long enough.
+    pub fn burn_out_flames(&mut self, model: &FireModel) {
+        let mut high_turns = HashMap::new();
+        for pos in self.positions().collect::<Vec<_>>() {
+            if self.get(&pos) != Some(&Tile::Fire(Intensity::High)) {
+                continue;
+            }
+            let turns = self.high_turns.get(&(pos.x, pos.y)).copied().unwrap_or(0) + 1;
+            if model.burnout.is_some_and(|burnout| turns >= burnout) {
+                self.set(&pos, Tile::Scorched);
+            } else {
+                high_turns.insert((pos.x, pos.y), turns);
+            }
+        }
+        self.high_turns = high_turns;
+    }
+
+    /// Fires that were knocked down since last turn start counting again.
+    fn forget_doused_flames(&mut self) {
+        let high_turns = std::mem::take(&mut self.high_turns);
+        self.high_turns = high_turns
+            .into_iter()
+            .filter(|((x, y), _)| {
+                self.get(&GamePosition {
+                    x: *x,
+                    y: *y,
+                    ..default()
+                }) == Some(&Tile::Fire(Intensity::High))
+            })
+            .collect();
+    }
+
+    /// The fire's move once the player ends their turn.
+    pub fn advance_fire<R: Rng + ?Sized>(&mut self, model: &FireModel, rng: &mut R) {
+        self.forget_doused_flames();
+        // Where the fire spreads is rolled before anything grows, and the new
+        // flames are only lit afterwards so they start Low for a whole turn
+        let targets = self.spread_targets(model, rng);
+        self.grow_flames();
+        self.spread_flames(&targets);
+        self.burn_out_flames(model);
+    }
+
+    /// What `advance_fire` may do to each tile, without rolling for it.
+    pub fn forecast(&self, model: &FireModel) -> Vec<(GamePosition, FireForecast)> {
+        let mut forecast: Vec<(GamePosition, FireForecast)> = self
+            .spread_chances(model)
+            .into_iter()
+            .map(|(pos, chance)| (pos, FireForecast::Ignites(chance)))
+            .collect();
+        let mut after = self.clone();
+        after.forget_doused_flames();
+        after.grow_flames();
+        after.burn_out_flames(model);
+        for pos in self.positions() {
+            let (Some(Tile::Fire(_)), Some(next)) = (self.get(&pos), after.get(&pos)) else {
+                continue;
+            };
+            if next == &Tile::Scorched {
+                forecast.push((pos, FireForecast::BurnsOut));
+            } else if self.get(&pos) != Some(next) {
+                forecast.push((pos, FireForecast::Grows));
+            }
+        }
+        forecast
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        outcome(self.tiles.iter().flatten())
+    }
+}
+
+/// What the fire may do to a tile at the end of the turn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FireForecast {
+    /// Catches fire with this chance.
+    Ignites(f32),
+    /// A flame that goes up an intensity.
+    Grows,
+    /// A High flame that leaves scorched ground behind.
+    BurnsOut,
+}
+
+/// A card played from the player's position, worked out up front so the game
+/// can animate it and the `Simulation` can apply it.
+#[derive(Clone)]
+pub struct CardPlay {
+    pub content_id: ContentID,
+    /// Where the player ends up.
+    pub to: GamePosition,
+    pub steps: Vec<EffectStep>,
+    /// The board once the card's effects are done, before the player steps
+    /// onto `to`.
+    pub board: Board,
+    pub energy: Energy,
+    pub water: Water,
+}
+
+impl CardPlay {
+    /// Returns `None` if the player can't afford the card or its way is
+    /// blocked. Hand and deck effects are left to the caller.
+    pub fn resolve(
+        content_id: &ContentID,
+        card_info: &CardInfo,
+        position: &GamePosition,
+        energy: &Energy,
+        water: &Water,
+        board: &Board,
+    ) -> Option<Self> {
+        if !can_afford(card_info, energy, water) {
+            return None;
+        }
+        let resolution = resolve_effects(&card_info.effects, position, board)?;
+        let cost = &card_info.resource_cost;
+        Some(Self {
+            content_id: content_id.clone(),
+            to: resolution.position,
+            steps: resolution.steps,
+            board: resolution.board,
+            energy: Energy {
+                current: energy.current - cost.energy + resolution.gained.energy,
+                ..energy.clone()
+            },
+            water: Water {
+                current: water.current - cost.water + resolution.gained.water,
+                ..water.clone()
+            },
+        })
+    }
+}
+
+/// A complete, headless copy of a level in progress.
+#[derive(Clone)]
+pub struct Simulation {
+    pub board: Board,
+    pub items: Vec<(i32, i32, Item)>,
+    pub position: GamePosition,
+    pub energy: Energy,
+    pub water: Water,
+    /// The draw pile; cards are drawn from the end.
+    pub deck: Vec<ContentID>,
+    pub recycled: Vec<ContentID>,
+    pub discarded: Vec<ContentID>,
+    pub hand: [Option<ContentID>; HAND_SIZE],
+    pub turn: u32,
+    pub fire: FireModel,
+}
+
+impl Simulation {
+    /// Sets up a level before the deck is shuffled and the first turn starts.
+    pub fn from_level(level: &Level) -> Self {
+        let mut board = Board::new(level.map_size.0, level.map_size.1);
+        for (x, y) in level.walls.iter() {
+            board.set(
+                &GamePosition {
+                    x: *x,
+                    y: *y,
+                    ..default()
+                },
+                Tile::Wall,
+            );
+        }
+        for (x, y, intensity) in level.flames.iter() {
+            board.set(
+                &GamePosition {
+                    x: *x,
+                    y: *y,
+                    ..default()
+                },
+                Tile::Fire(intensity.clone()),
+            );
+        }
+        for (x, y, tile) in level.terrain.iter() {
+            board.set(
+                &GamePosition {
+                    x: *x,
+                    y: *y,
+                    ..default()
+                },
+                tile.clone(),
+            );
+        }
+        Self {
+            board,
+            items: level.items.clone(),
+            position: level.start.clone(),
+            energy: Energy {
+                current: 0,
+                maxium: 10,
+            },
+            water: Water {
+                current: 0,
+                maxium: 10,
+            },
+            deck: level.deck_list.iter().map(|id| ContentID(*id)).collect(),
+            recycled: Vec::new(),
+            discarded: Vec::new(),
+            hand: Default::default(),
+            turn: 0,
+            fire: FireModel::default(),
+        }
+    }
+
+    /// Draws a specific card instead of the top of the deck, as if the
+    /// shuffle had put it there. Returns `false` if it can't be drawn.
+    pub fn draw_card(&mut self, content_id: &ContentID) -> bool {
+        let Some(slot) = self.hand.iter().position(|slot| slot.is_none()) else {
+            return false;
+        };
+        if self.deck.is_empty() {
+            self.deck.append(&mut self.recycled);
+        }
+        let Some(index) = self.deck.iter().position(|card| card == content_id) else {
+            return false;
+        };
+        self.hand[slot] = Some(self.deck.remove(index));
+        true
+    }
+
+    /// Restores resources at the start of a turn, before any cards are drawn.
+    pub fn begin_turn(&mut self) {
+        self.turn += 1;
+        restore_for_turn(&mut self.energy, &mut self.water);
+    }
+
+    /// Plays the card in the hand slot, or returns `None` if it can't be played.
+    /// Draws come off the top without reshuffling, and chosen discards aren't
+    /// modelled since they depend on the player.
+    pub fn play_card(&mut self, slot: usize, card_infos: &CardInfoMap) -> Option<CardPlay> {
+        let content_id = self.hand.get(slot)?.clone()?;
+        let card_info = card_infos.0.get(&content_id)?;
+        let play = CardPlay::resolve(
+            &content_id,
+            card_info,
+            &self.position,
+            &self.energy,
+            &self.water,
+            &self.board,
+        )?;
+        self.hand[slot] = None;
+        self.energy = play.energy.clone();
+        self.water = play.water.clone();
+        self.board = play.board.clone();
+        self.move_to(play.to.clone());
+        if card_info.effects.contains(&CardEffect::Exhaust) {
+            self.discarded.push(content_id.clone());
+        } else {
+            self.recycled.push(content_id.clone());
+        }
+        for effect in card_info.effects.iter() {
+            match effect {
+                CardEffect::Draw(count) => {
+                    for _ in 0..*count {
+                        // A full hand draws nothing, same as in the game
+                        let Some(slot) = self.hand.iter().position(|slot| slot.is_none()) else {
+                            break;
+                        };
+                        if self.deck.is_empty() {
+                            self.deck.append(&mut self.recycled);
+                        }
+                        self.hand[slot] = self.deck.pop();
+                    }
+                }
+                CardEffect::RecycleHand => {
+                    self.recycled
+                        .extend(self.hand.iter_mut().filter_map(Option::take));
+                }
+                // The board side was resolved above
+                _ => {}
+            }
+        }
+        Some(play)
+    }
+
+    /// Moves the player, putting out the flame underfoot and collecting items.
+    fn move_to(&mut self, position: GamePosition) {
+        if self.board.step_on(&position) {
+            self.water.current = self.water.current.max(self.water.maxium);
+        }
+        let (x, y) = (position.x, position.y);
+        let (picked_up, remaining) = self
+            .items
+            .drain(..)
+            .partition(|(item_x, item_y, _)| *item_x == x && *item_y == y);
+        self.items = remaining;
+        for (_, _, item) in picked_up {
+            match item {
+                Item::Water => self.water.current += 1,
+                Item::Energy => self.energy.current += 1,
+                Item::Card(content_id) => self.deck.push(content_id),
+            }
+        }
+        self.position = position;
+    }
+
+    /// The fire's move once the player ends their turn, then the start of the
+    /// next one. The new hand is left to the caller, since the solver picks
+    /// its draws instead of shuffling.
+    pub fn end_turn<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.board.advance_fire(&self.fire, rng);
+        self.begin_turn();
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.board.outcome()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: ContentID = ContentID(1);
+    const SPRAY: ContentID = ContentID(2);
+
+    fn card(name: &str, energy: i32, water: i32, effects: Vec<CardEffect>) -> CardInfo {
+        CardInfo {
+            resource_cost: ResourceInfo { energy, water },
+            texture_index: 0,
+            name: name.to_string(),
+            description: String::new(),
+            effects,
+            upgrades: Vec::new(),
+        }
+    }
+
+    fn card_infos() -> CardInfoMap {
+        CardInfoMap(HashMap::from([
+            (
+                STEP,
+                card("Step", 1, 0, vec![CardEffect::Move(TileTarget::FacingDist(1))]),
+            ),
+            (
+                SPRAY,
+                card(
+                    "Spray",
+                    0,
+                    1,
+                    vec![CardEffect::Extinguish(DamageInfo {
+                        damage_target: TileTarget::FacingDist(1),
+                        amount: 1,
+                    })],
+                ),
+            ),
+        ]))
+    }
+
+    fn at(x: i32, y: i32) -> GamePosition {
+        GamePosition { x, y, ..default() }
+    }
+
+    fn simulation(puzzle: &str) -> Simulation {
+        let mut sim = Simulation::from_level(&parse_puzzle(puzzle).expect("Puzzle should parse"));
+        sim.begin_turn();
+        sim
+    }
+
+    #[test]
+    fn outcome_follows_fire_and_open_ground() {
+        let mut board = Board::new(2, 1);
+        assert_eq!(board.outcome(), Outcome::Success);
+        board.set(&at(1, 1), Tile::Fire(Intensity::Low));
+        assert_eq!(board.outcome(), Outcome::InProgress);
+        board.set(&at(2, 1), Tile::Fire(Intensity::High));
+        assert_eq!(board.outcome(), Outcome::Failure);
+        board.set(&at(2, 1), Tile::Puddle);
+        assert_eq!(board.outcome(), Outcome::InProgress);
+    }
+
+    #[test]
+    fn playing_a_move_spends_energy_and_stamps_out_the_flame_underfoot() {
+        let mut sim = simulation("deck: 1\nmap:\n.\n1\n^\n");
+        sim.hand[0] = Some(STEP);
+        let play = sim.play_card(0, &card_infos()).expect("Step should be playable");
+        assert_eq!(play.content_id, STEP);
+        assert_eq!((sim.position.x, sim.position.y), (1, 2));
+        assert_eq!(sim.board.get(&at(1, 2)), Some(&Tile::Empty));
+        assert_eq!(sim.energy.current, 4);
+        assert_eq!(sim.hand[0], None);
+        assert_eq!(sim.recycled, vec![STEP]);
+        assert_eq!(sim.outcome(), Outcome::Success);
+    }
+
+    #[test]
+    fn blocked_or_unaffordable_cards_leave_the_level_alone() {
+        let mut sim = simulation("deck: 1\nmap:\n#\n^\n");
+        sim.hand[0] = Some(STEP);
+        assert!(sim.play_card(0, &card_infos()).is_none());
+        assert_eq!(sim.hand[0], Some(STEP));
+        assert_eq!(sim.energy.current, 5);
+
+        let mut sim = simulation("deck: 2\nmap:\n1\n^\n");
+        sim.water.current = 0;
+        sim.hand[0] = Some(SPRAY);
+        assert!(sim.play_card(0, &card_infos()).is_none());
+        assert_eq!(sim.board.get(&at(1, 2)), Some(&Tile::Fire(Intensity::Low)));
+    }
+
+    #[test]
+    fn spraying_knocks_a_fire_down_one_intensity() {
+        let mut sim = simulation("deck: 2\nmap:\n2\n^\n");
+        sim.hand[0] = Some(SPRAY);
+        sim.hand[1] = Some(SPRAY);
+        sim.play_card(0, &card_infos()).expect("Spray should be playable");
+        assert_eq!(sim.board.get(&at(1, 2)), Some(&Tile::Fire(Intensity::Low)));
+        sim.play_card(1, &card_infos()).expect("Spray should be playable");
+        assert_eq!(sim.board.get(&at(1, 2)), Some(&Tile::Empty));
+        assert_eq!(sim.water.current, 3);
+        assert_eq!(sim.outcome(), Outcome::Success);
+    }
+
+    #[test]
+    fn end_turn_grows_old_flames_and_spreads_new_ones_at_low() {
+        let mut sim = simulation("deck: 1\nmap:\n...\n.3.\n1..\n^..\n");
+        sim.energy.current = 0;
+        sim.end_turn(&mut GameRng::new(0));
+        assert_eq!(sim.turn, 2);
+        assert_eq!(sim.energy.current, 5);
+        // The Low fire grows, and the High fire spreads without the new
+        // flames growing in the same turn
+        assert_eq!(sim.board.get(&at(1, 2)), Some(&Tile::Fire(Intensity::Medium)));
+        assert_eq!(sim.board.get(&at(2, 3)), Some(&Tile::Fire(Intensity::High)));
+        for neighbor in [at(2, 4), at(3, 3), at(2, 2)] {
+            assert_eq!(sim.board.get(&neighbor), Some(&Tile::Fire(Intensity::Low)));
+        }
+        assert_eq!(sim.board.get(&at(3, 1)), Some(&Tile::Empty));
+        sim.end_turn(&mut GameRng::new(0));
+        assert_eq!(sim.board.get(&at(2, 4)), Some(&Tile::Fire(Intensity::Medium)));
+    }
+
+    #[test]
+    fn end_turn_burns_out_high_flames_into_scorched_ground() {
+        let mut sim = simulation("deck: 1\nmap:\n3.\n^.\n");
+        sim.fire = FireModel {
+            spread_chance: [0.0, 0.0, 0.0],
+            burnout: Some(2),
+            ..default()
+        };
+        let mut rng = GameRng::new(0);
+        sim.end_turn(&mut rng);
+        assert_eq!(sim.board.get(&at(1, 2)), Some(&Tile::Fire(Intensity::High)));
+        sim.end_turn(&mut rng);
+        assert_eq!(sim.board.get(&at(1, 2)), Some(&Tile::Scorched));
+        assert_eq!(sim.outcome(), Outcome::Success);
+    }
+}
@@ -0,0 +1,95 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+pub const ROGUE_SAVE_KEY: &str = "rogue_run.ron";
+
+/// A Rogue run as it stood at the start of its current level.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RogueSave {
+    pub seed: u64,
+    pub rng_state: u64,
+    pub level_index: i32,
+    pub deck_list: Vec<DeckCard>,
+    pub energy: Energy,
+    pub water: Water,
+}
+
+impl RogueSave {
+    pub fn load() -> Option<Self> {
+        let text = read_save(ROGUE_SAVE_KEY)?;
+        match ron::from_str(&text) {
+            Ok(save) => Some(save),
+            Err(error) => {
+                warn!("Ignoring unreadable Rogue save: {}", error);
+                None
+            }
+        }
+    }
+
+    pub fn exists() -> bool {
+        Self::load().is_some()
+    }
+
+    pub fn store(&self) {
+        match ron::to_string(self) {
+            Ok(text) => write_save(ROGUE_SAVE_KEY, &text),
+            Err(error) => error!("Failed to serialize the Rogue run: {}", error),
+        }
+    }
+
+    pub fn delete() {
+        delete_save(ROGUE_SAVE_KEY);
+    }
+}
+
+/// A save waiting to be restored once the next level loads.
+#[derive(Resource)]
+pub struct ContinueRun(pub RogueSave);
+
+pub fn restore_saved_run(
+    mut commands: Commands,
+    continue_run: Option<Res<ContinueRun>>,
+    mut rng: ResMut<GameRng>,
+    mut checkpoint: ResMut<LevelCheckpoint>,
+    mut level_index: ResMut<LevelIndex>,
+    mut deck_list: ResMut<DeckList>,
+    mut player: Query<(&mut Energy, &mut Water), With<Player>>,
+) {
+    let Some(continue_run) = continue_run else {
+        return;
+    };
+    let save = continue_run.0.clone();
+    info!("Continuing Rogue run at level {}", save.level_index);
+    *rng = GameRng::new(save.seed);
+    checkpoint.pending = Some(save.rng_state);
+    level_index.0 = save.level_index;
+    deck_list.0 = save.deck_list;
+    let (mut energy, mut water) = player.get_single_mut().expect("Should be exactly 1 player");
+    *energy = save.energy;
+    *water = save.water;
+    commands.remove_resource::<ContinueRun>();
+}
+
+pub fn save_rogue_run(
+    mode: Res<GameMode>,
+    rng: Res<GameRng>,
+    checkpoint: Res<LevelCheckpoint>,
+    playback: Option<Res<Playback>>,
+    level_index: Res<LevelIndex>,
+    deck_list: Res<DeckList>,
+    player: Query<(&Energy, &Water), With<Player>>,
+) {
+    if *mode != GameMode::Rogue || playback.is_some() {
+        return;
+    }
+    let (energy, water) = player.get_single().expect("Should be exactly 1 player");
+    RogueSave {
+        seed: rng.seed,
+        rng_state: checkpoint.state,
+        level_index: level_index.0,
+        deck_list: deck_list.0.clone(),
+        energy: energy.clone(),
+        water: water.clone(),
+    }
+    .store();
+}
@@ -0,0 +1,207 @@
+use super::*;
+
+/// The turns and card plays a puzzle can be finished in, for the stars.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Par {
+    pub turns: u32,
+    pub cards: u32,
+}
+
+/// What the player has done so far this level.
+#[derive(Clone, Default, Resource)]
+pub struct LevelStats {
+    pub turns: u32,
+    pub cards_played: u32,
+    /// The most tiles that were on fire at once.
+    pub peak_fire: u32,
+    pub items_at_start: u32,
+}
+
+/// How a finished level went, shown on the result menu.
+#[derive(Clone, Debug)]
+pub struct Score {
+    pub won: bool,
+    pub turns: u32,
+    pub cards_played: u32,
+    pub peak_fire: u32,
+    pub energy_left: i32,
+    pub water_left: i32,
+    pub items_collected: u32,
+    pub par: Option<Par>,
+}
+
+/// The score of the level that ended last.
+#[derive(Default, Resource)]
+pub struct LastScore(pub Option<Score>);
+
+const BASE_POINTS: i32 = 1000;
+
+impl Score {
+    /// Each line of the score with the points it adds or takes away.
+    pub fn breakdown(&self) -> Vec<(String, i32)> {
+        vec![
+            ("Fire put out".to_string(), BASE_POINTS),
+            (format!("Turns taken: {}", self.turns), -100 * self.turns as i32),
+            (format!("Cards played: {}", self.cards_played), -20 * self.cards_played as i32),
+            (format!("Peak fire: {} tile(s)", self.peak_fire), -25 * self.peak_fire as i32),
+            (format!("Energy left: {}", self.energy_left), 10 * self.energy_left),
+            (format!("Water left: {}", self.water_left), 20 * self.water_left),
+            (format!("Items collected: {}", self.items_collected), 50 * self.items_collected as i32),
+        ]
+    }
+
+    pub fn points(&self) -> i32 {
+        if !self.won {
+            return 0;
+        }
+        self.breakdown()
+            .iter()
+            .map(|(_, points)| points)
+            .sum::<i32>()
+            .max(0)
+    }
+
+    /// One star for a win, two for making par on turns and three for also
+    /// making par on cards. Levels without a par only ever give one.
+    pub fn stars(&self) -> u32 {
+        if !self.won {
+            return 0;
+        }
+        match &self.par {
+            Some(par) if self.turns <= par.turns && self.cards_played <= par.cards => 3,
+            Some(par) if self.turns <= par.turns => 2,
+            _ => 1,
+        }
+    }
+}
+
+pub fn reset_level_stats(
+    mut stats: ResMut<LevelStats>,
+    items: Query<(), (With<Item>, With<GamePosition>)>,
+) {
+    *stats = LevelStats {
+        items_at_start: items.iter().count() as u32,
+        ..default()
+    };
+}
+
+pub fn count_turn(mut stats: ResMut<LevelStats>) {
+    stats.turns += 1;
+}
+
+pub fn count_plays(mut stats: ResMut<LevelStats>, actions: Query<&CardActionType>) {
+    let plays = actions
+        .iter()
+        .filter(|action| matches!(action, CardActionType::Play(_)))
+        .count();
+    stats.cards_played += plays as u32;
+}
+
+/// Takes back the play that `undo_last_play` is about to undo.
+pub fn uncount_undos(
+    mut stats: ResMut<LevelStats>,
+    mut events: EventReader<UndoClicked>,
+    undo_stack: Res<UndoStack>,
+) {
+    if events.read().count() > 0 && !undo_stack.0.is_empty() {
+        stats.cards_played = stats.cards_played.saturating_sub(1);
+    }
+}
+
+pub fn track_peak_fire(
+    mut stats: ResMut<LevelStats>,
+    tiles: Query<&Tile>,
+    changes: Query<(), Changed<Tile>>,
+) {
+    if changes.is_empty() {
+        return;
+    }
+    let fire = tiles
+        .iter()
+        .filter(|tile| matches!(tile, Tile::Fire(_)))
+        .count() as u32;
+    stats.peak_fire = stats.peak_fire.max(fire);
+}
+
+/// Scores the level once it is won or lost.
+pub fn score_level(
+    mut last_score: ResMut<LastScore>,
+    stats: Res<LevelStats>,
+    map: Res<MapParameters>,
+    player: Query<(&Energy, &Water), With<Player>>,
+    items: Query<(), (With<Item>, With<GamePosition>)>,
+    tiles: Query<&Tile>,
+    changes: Query<(), Changed<Tile>>,
+) {
+    if changes.is_empty() {
+        return;
+    }
+    let won = match outcome(tiles.iter()) {
+        Outcome::Success => true,
+        Outcome::Failure => false,
+        Outcome::InProgress => return,
+    };
+    let (energy, water) = player.get_single().expect("Should be exactly 1 player");
+    let score = Score {
+        won,
+        turns: stats.turns,
+        cards_played: stats.cards_played,
+        peak_fire: stats.peak_fire,
+        energy_left: energy.current,
+        water_left: water.current,
+        items_collected: stats.items_at_start.saturating_sub(items.iter().count() as u32),
+        par: map.par.clone(),
+    };
+    info!("Level scored {} with {} star(s)", score.points(), score.stars());
+    last_score.0 = Some(score);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(won: bool, turns: u32, cards_played: u32, par: Option<Par>) -> Score {
+        Score {
+            won,
+            turns,
+            cards_played,
+            peak_fire: 2,
+            energy_left: 1,
+            water_left: 1,
+            items_collected: 1,
+            par,
+        }
+    }
+
+    const PAR: Option<Par> = Some(Par { turns: 2, cards: 5 });
+
+    #[test]
+    fn stars_follow_par() {
+        assert_eq!(score(true, 2, 5, PAR).stars(), 3);
+        assert_eq!(score(true, 1, 3, PAR).stars(), 3);
+        assert_eq!(score(true, 2, 6, PAR).stars(), 2);
+        assert_eq!(score(true, 3, 5, PAR).stars(), 1);
+        assert_eq!(score(false, 1, 1, PAR).stars(), 0);
+    }
+
+    #[test]
+    fn levels_without_par_give_one_star() {
+        assert_eq!(score(true, 1, 1, None).stars(), 1);
+        assert_eq!(score(false, 1, 1, None).stars(), 0);
+        // Par only decides the stars, never the points
+        assert_eq!(score(true, 1, 1, None).points(), score(true, 1, 1, PAR).points());
+    }
+
+    #[test]
+    fn points_add_up_the_breakdown() {
+        let won = score(true, 2, 5, PAR);
+        // 1000 - 200 - 100 - 50 + 10 + 20 + 50
+        assert_eq!(won.points(), 730);
+        assert_eq!(
+            won.points(),
+            won.breakdown().iter().map(|(_, points)| points).sum::<i32>()
+        );
+        assert_eq!(score(false, 2, 5, PAR).points(), 0);
+        assert_eq!(score(true, 20, 50, PAR).points(), 0);
+    }
+}
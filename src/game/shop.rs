@@ -0,0 +1,67 @@
+use super::*;
+
+/// How many cards the shop offers between Rogue levels.
+pub const SHOP_OFFER_SIZE: usize = 3;
+
+/// The shop won't remove cards below this many.
+pub const MIN_DECK_SIZE: usize = 3;
+
+/// The cards on offer in the shop between Rogue levels.
+#[derive(Default, Resource)]
+pub struct ShopOffer(pub Vec<ContentID>);
+
+/// Picks distinct cards for the shop, with the same odds as card pickups.
+pub fn stock_shop(
+    mut offer: ResMut<ShopOffer>,
+    card_infos: Res<CardInfoMap>,
+    mut rng: ResMut<GameRng>,
+) {
+    let mut table = ItemTable::from_card_infos(&card_infos);
+    table.0.retain(|(item, _)| matches!(item, Item::Card(_)));
+    offer.0.clear();
+    while offer.0.len() < SHOP_OFFER_SIZE {
+        let Some(Item::Card(content_id)) = table.pick(&mut *rng) else {
+            break;
+        };
+        table
+            .0
+            .retain(|(item, _)| !matches!(item, Item::Card(id) if *id == content_id));
+        offer.0.push(content_id);
+    }
+}
+
+/// Removes one copy of the card, unless that would leave the deck too thin.
+pub fn remove_from_deck(deck_list: &mut DeckList, card: &DeckCard) -> bool {
+    if deck_list.0.len() <= MIN_DECK_SIZE {
+        return false;
+    }
+    match deck_list.0.iter().position(|entry| entry == card) {
+        Some(index) => {
+            deck_list.0.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Upgrades one copy of the card, if it has an upgrade left.
+pub fn upgrade_in_deck(
+    deck_list: &mut DeckList,
+    card: &DeckCard,
+    card_infos: &CardInfoMap,
+) -> bool {
+    let can_upgrade = card_infos
+        .0
+        .get(&card.content_id)
+        .is_some_and(|card_info| card.upgrade < card_info.max_upgrade());
+    if !can_upgrade {
+        return false;
+    }
+    match deck_list.0.iter_mut().find(|entry| *entry == card) {
+        Some(entry) => {
+            entry.upgrade += 1;
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,454 @@
+use super::*;
+use std::collections::HashSet;
+
+/// One step of a solution. Draws are listed explicitly because the solver
+/// decides the deck order instead of shuffling.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolverStep {
+    Draw(ContentID),
+    Play(ContentID),
+    EndTurn,
+}
+
+/// How the solver treats the shuffle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeckOrder {
+    /// The cards arrive in whatever order suits the player.
+    BestCase,
+    /// The cards arrive in whatever order hurts the player the most.
+    WorstCase,
+}
+
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub turns: u32,
+    /// An example line of play. For the worst case this follows the draws
+    /// that took the most turns to win from; every other draw also wins.
+    pub steps: Vec<SolverStep>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SolverReport {
+    pub best_case: Option<Solution>,
+    pub worst_case: Option<Solution>,
+}
+
+type StateKey = (Vec<u8>, Vec<(i32, i32)>, (i32, i32, u8), (i32, i32), [Vec<usize>; 4]);
+
+fn state_key(sim: &Simulation) -> StateKey {
+    let tiles = sim
+        .board
+        .tiles
+        .iter()
+        .flatten()
+        .map(|tile| match tile {
+            Tile::Empty => 0,
+            Tile::Wall => 1,
+            Tile::Fire(intensity) => 1 + intensity.clone() as u8,
+            Tile::Puddle => 5,
+            Tile::Fuel => 6,
+            Tile::Door => 7,
+            Tile::Scorched => 8,
+        })
+        .collect();
+    let items = sim.items.iter().map(|(x, y, _)| (*x, *y)).collect();
+    let facing = match sim.position.d {
+        GameDirection::Up => 0,
+        GameDirection::Down => 1,
+        GameDirection::Left => 2,
+        GameDirection::Right => 3,
+    };
+    let sorted = |cards: &mut dyn Iterator<Item = &ContentID>| {
+        let mut ids: Vec<usize> = cards.map(|card| card.0).collect();
+        ids.sort_unstable();
+        ids
+    };
+    (
+        tiles,
+        items,
+        (sim.position.x, sim.position.y, facing),
+        (sim.energy.current, sim.water.current),
+        [
+            sorted(&mut sim.deck.iter()),
+            sorted(&mut sim.recycled.iter()),
+            sorted(&mut sim.discarded.iter()),
+            sorted(&mut sim.hand.iter().flatten()),
+        ],
+    )
+}
+
+pub struct Solver<'a> {
+    card_infos: &'a CardInfoMap,
+    order: DeckOrder,
+    memo: HashMap<(StateKey, u32), Option<Vec<SolverStep>>>,
+    /// Puzzle fire never leaves anything to chance, so this is never rolled.
+    fire_rng: GameRng,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(card_infos: &'a CardInfoMap, order: DeckOrder) -> Self {
+        Self {
+            card_infos,
+            order,
+            memo: HashMap::new(),
+            fire_rng: GameRng::new(0),
+        }
+    }
+
+    /// Finds the fewest turns needed to clear the level, trying every turn
+    /// count up to `max_turns`.
+    pub fn solve(&mut self, level: &Level, max_turns: u32) -> Option<Solution> {
+        let mut sim = Simulation::from_level(level);
+        sim.begin_turn();
+        for turns in 1..=max_turns {
+            self.memo.clear();
+            if let Some(steps) = self.after_draws(&sim, turns) {
+                return Some(Solution { turns, steps });
+            }
+        }
+        None
+    }
+
+    /// Every distinct hand the draws can produce at the start of a turn.
+    fn draw_outcomes(&self, sim: &Simulation) -> Vec<(Simulation, Vec<SolverStep>)> {
+        let mut outcomes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![(sim.clone(), Vec::new())];
+        while let Some((sim, steps)) = pending.pop() {
+            let has_room = sim.hand.iter().any(|slot| slot.is_none());
+            let mut pile = if sim.deck.is_empty() {
+                sim.recycled.clone()
+            } else {
+                sim.deck.clone()
+            };
+            if !has_room || pile.is_empty() {
+                if seen.insert(state_key(&sim)) {
+                    outcomes.push((sim, steps));
+                }
+                continue;
+            }
+            pile.sort_unstable_by_key(|card| card.0);
+            pile.dedup();
+            for content_id in pile {
+                let mut next = sim.clone();
+                next.draw_card(&content_id);
+                let mut next_steps = steps.clone();
+                next_steps.push(SolverStep::Draw(content_id));
+                pending.push((next, next_steps));
+            }
+        }
+        outcomes
+    }
+
+    /// Whether the level can be cleared within `turns_left` turns once the
+    /// cards for the current turn have been drawn.
+    fn after_draws(&mut self, sim: &Simulation, turns_left: u32) -> Option<Vec<SolverStep>> {
+        let outcomes = self.draw_outcomes(sim);
+        let turns_taken = |steps: &[SolverStep]| {
+            steps
+                .iter()
+                .filter(|step| **step == SolverStep::EndTurn)
+                .count()
+        };
+        let mut slowest: Option<Vec<SolverStep>> = None;
+        for (drawn, draw_steps) in outcomes {
+            match self.play_turn(&drawn, turns_left) {
+                Some(steps) => {
+                    let mut all_steps = draw_steps;
+                    all_steps.extend(steps);
+                    if self.order == DeckOrder::BestCase {
+                        return Some(all_steps);
+                    }
+                    let slower = match &slowest {
+                        Some(slowest) => turns_taken(&all_steps) > turns_taken(slowest),
+                        None => true,
+                    };
+                    if slower {
+                        slowest = Some(all_steps);
+                    }
+                }
+                None => {
+                    if self.order == DeckOrder::WorstCase {
+                        return None;
+                    }
+                }
+            }
+        }
+        slowest
+    }
+
+    /// Tries every sequence of plays this turn, then ending the turn.
+    fn play_turn(&mut self, sim: &Simulation, turns_left: u32) -> Option<Vec<SolverStep>> {
+        let key = (state_key(sim), turns_left);
+        if let Some(result) = self.memo.get(&key) {
+            return result.clone();
+        }
+        let mut seen = HashSet::new();
+        seen.insert(state_key(sim));
+        let mut pending = vec![(sim.clone(), Vec::new())];
+        let mut result = None;
+        'search: while let Some((sim, steps)) = pending.pop() {
+            for slot in 0..HAND_SIZE {
+                let mut next = sim.clone();
+                let Some(play) = next.play_card(slot, self.card_infos) else {
+                    continue;
+                };
+                if !seen.insert(state_key(&next)) {
+                    continue;
+                }
+                let mut next_steps = steps.clone();
+                next_steps.push(SolverStep::Play(play.content_id));
+                match next.outcome() {
+                    Outcome::Success => {
+                        result = Some(next_steps);
+                        break 'search;
+                    }
+                    Outcome::Failure => continue,
+                    Outcome::InProgress => pending.push((next, next_steps)),
+                }
+            }
+            if turns_left > 1 {
+                let mut next = sim.clone();
+                next.end_turn(&mut self.fire_rng);
+                if next.outcome() == Outcome::Failure {
+                    continue;
+                }
+                if let Some(rest) = self.after_draws(&next, turns_left - 1) {
+                    let mut all_steps = steps;
+                    all_steps.push(SolverStep::EndTurn);
+                    all_steps.extend(rest);
+                    result = Some(all_steps);
+                    break 'search;
+                }
+            }
+        }
+        self.memo.insert(key, result.clone());
+        result
+    }
+}
+
+pub fn solve_level(level: &Level, card_infos: &CardInfoMap, max_turns: u32) -> SolverReport {
+    let best_case = Solver::new(card_infos, DeckOrder::BestCase).solve(level, max_turns);
+    let worst_case = match best_case {
+        Some(_) => Solver::new(card_infos, DeckOrder::WorstCase).solve(level, max_turns),
+        None => None,
+    };
+    SolverReport {
+        best_case,
+        worst_case,
+    }
+}
+
+/// Solves every puzzle on disk and prints a report. Returns `false` if any
+/// puzzle could not be loaded or is unwinnable.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn check_puzzles(max_turns: u32) -> bool {
+    let assets = std::path::Path::new("assets");
+    let card_infos = match std::fs::read(assets.join(CARD_DEFINITIONS_PATH))
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| {
+            ron::de::from_bytes::<CardDefinitions>(&bytes).map_err(|error| error.to_string())
+        }) {
+        Ok(definitions) => definitions.card_info_map(),
+        Err(error) => {
+            println!("Could not load {}: {}", CARD_DEFINITIONS_PATH, error);
+            return false;
+        }
+    };
+    let mut paths: Vec<_> = match std::fs::read_dir(assets.join(PUZZLE_FOLDER)) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "puzzle"))
+            .collect(),
+        Err(error) => {
+            println!("Could not read {}: {}", PUZZLE_FOLDER, error);
+            return false;
+        }
+    };
+    paths.sort();
+    let mut all_solvable = true;
+    for path in paths {
+        let level = match std::fs::read_to_string(&path)
+            .map_err(PuzzleError::from)
+            .and_then(|text| parse_puzzle(&text))
+        {
+            Ok(level) => level,
+            Err(error) => {
+                println!("{}: {}", path.display(), error);
+                all_solvable = false;
+                continue;
+            }
+        };
+        let report = solve_level(&level, &card_infos, max_turns);
+        match (&report.best_case, &report.worst_case) {
+            (Some(best), worst) => {
+                println!(
+                    "{} ({}): best case {} turn(s), worst case {}",
+                    path.display(),
+                    level.name,
+                    best.turns,
+                    worst
+                        .as_ref()
+                        .map(|worst| format!("{} turn(s)", worst.turns))
+                        .unwrap_or_else(|| format!("not within {} turns", max_turns)),
+                );
+                println!("    {:?}", best.steps);
+                // A par the solver can't reach would make three stars impossible
+                if level.par.as_ref().is_some_and(|par| par.turns < best.turns) {
+                    println!("    par is fewer turns than the best case");
+                    all_solvable = false;
+                }
+            }
+            (None, _) => {
+                println!(
+                    "{} ({}): UNSOLVABLE within {} turns",
+                    path.display(),
+                    level.name,
+                    max_turns
+                );
+                all_solvable = false;
+            }
+        }
+    }
+    all_solvable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAIT: ContentID = ContentID(1);
+    const HOSE: ContentID = ContentID(2);
+
+    fn card(name: &str, effects: Vec<CardEffect>) -> CardInfo {
+        CardInfo {
+            resource_cost: ResourceInfo {
+                energy: 0,
+                water: 0,
+            },
+            texture_index: 0,
+            name: name.to_string(),
+            description: String::new(),
+            effects,
+            upgrades: Vec::new(),
+        }
+    }
+
+    /// A card that does nothing, and a hose that reaches over the wall.
+    fn card_infos() -> CardInfoMap {
+        CardInfoMap(HashMap::from([
+            (WAIT, card("Wait", vec![CardEffect::Rotate(Rotation::None)])),
+            (
+                HOSE,
+                card(
+                    "Hose",
+                    vec![CardEffect::Extinguish(DamageInfo {
+                        damage_target: TileTarget::FacingDist(2),
+                        amount: 3,
+                    })],
+                ),
+            ),
+        ]))
+    }
+
+    /// Only the hose can reach the fire, so it depends on being drawn.
+    fn behind_the_wall() -> Level {
+        parse_puzzle("deck: 1 1 1 1 1 2\nmap:\n1\n#\n^\n").expect("Puzzle should parse")
+    }
+
+    /// Plays a solution through the simulation, checking every step is legal.
+    fn replay(level: &Level, steps: &[SolverStep]) -> Simulation {
+        let card_infos = card_infos();
+        let mut sim = Simulation::from_level(level);
+        sim.begin_turn();
+        for step in steps.iter() {
+            match step {
+                SolverStep::Draw(content_id) => assert!(sim.draw_card(content_id)),
+                SolverStep::Play(content_id) => {
+                    let slot = sim
+                        .hand
+                        .iter()
+                        .position(|card| card.as_ref() == Some(content_id))
+                        .expect("Played card should be in the hand");
+                    assert!(sim.play_card(slot, &card_infos).is_some());
+                }
+                SolverStep::EndTurn => sim.end_turn(&mut GameRng::new(0)),
+            }
+        }
+        sim
+    }
+
+    #[test]
+    fn best_case_draws_the_hose_straight_away() {
+        let level = behind_the_wall();
+        let solution = Solver::new(&card_infos(), DeckOrder::BestCase)
+            .solve(&level, 5)
+            .expect("Puzzle should be solvable");
+        assert_eq!(solution.turns, 1);
+        assert_eq!(
+            solution.steps,
+            [
+                SolverStep::Draw(HOSE),
+                SolverStep::Draw(WAIT),
+                SolverStep::Draw(WAIT),
+                SolverStep::Draw(WAIT),
+                SolverStep::Draw(WAIT),
+                SolverStep::Play(HOSE),
+            ]
+        );
+        assert_eq!(replay(&level, &solution.steps).outcome(), Outcome::Success);
+    }
+
+    #[test]
+    fn worst_case_waits_a_turn_for_the_hose() {
+        let level = behind_the_wall();
+        let solution = Solver::new(&card_infos(), DeckOrder::WorstCase)
+            .solve(&level, 5)
+            .expect("Puzzle should be solvable");
+        assert_eq!(solution.turns, 2);
+        // Five waits leave the hose for next turn, by when the fire has grown
+        assert_eq!(
+            solution.steps,
+            [
+                SolverStep::Draw(WAIT),
+                SolverStep::Draw(WAIT),
+                SolverStep::Draw(WAIT),
+                SolverStep::Draw(WAIT),
+                SolverStep::Draw(WAIT),
+                SolverStep::Play(WAIT),
+                SolverStep::EndTurn,
+                SolverStep::Draw(HOSE),
+                SolverStep::Play(HOSE),
+            ]
+        );
+        let sim = replay(&level, &solution.steps);
+        assert_eq!(sim.outcome(), Outcome::Success);
+        assert_eq!(sim.turn, 2);
+    }
+
+    #[test]
+    fn solve_level_reports_both_cases_within_the_turn_limit() {
+        let level = behind_the_wall();
+        let report = solve_level(&level, &card_infos(), 5);
+        assert_eq!(report.best_case.map(|solution| solution.turns), Some(1));
+        assert_eq!(report.worst_case.map(|solution| solution.turns), Some(2));
+
+        let report = solve_level(&level, &card_infos(), 1);
+        assert_eq!(report.best_case.map(|solution| solution.turns), Some(1));
+        assert!(report.worst_case.is_none());
+    }
+
+    #[test]
+    fn a_fire_out_of_reach_is_unsolvable() {
+        let level = parse_puzzle("deck: 1 1\nmap:\n1\n#\n^\n").expect("Puzzle should parse");
+        let report = solve_level(&level, &card_infos(), 3);
+        assert!(report.best_case.is_none());
+        assert!(report.worst_case.is_none());
+    }
+
+    #[test]
+    fn bundled_puzzles_are_solvable_within_par() {
+        assert!(check_puzzles(crate::SOLVER_MAX_TURNS));
+    }
+}
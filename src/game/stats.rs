@@ -1,12 +1,13 @@
 use super::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Energy {
     pub current: i32,
     pub maxium: i32,
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Water {
     pub current: i32,
     pub maxium: i32,
@@ -0,0 +1,57 @@
+use super::*;
+
+// Small key/value persistence for saves. Native builds keep one file per key
+// in a `saves` folder next to where the game was started; the web build uses
+// the browser's localStorage.
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_FOLDER: &str = "saves";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path(key: &str) -> std::path::PathBuf {
+    std::path::Path::new(SAVE_FOLDER).join(key)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_save(key: &str) -> Option<String> {
+    std::fs::read_to_string(save_path(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_save(key: &str, contents: &str) {
+    let result = std::fs::create_dir_all(SAVE_FOLDER)
+        .and_then(|_| std::fs::write(save_path(key), contents));
+    if let Err(error) = result {
+        error!("Failed to write save {}: {}", key, error);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn delete_save(key: &str) {
+    let _ = std::fs::remove_file(save_path(key));
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_save(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_save(key: &str, contents: &str) {
+    let written = local_storage().is_some_and(|storage| storage.set_item(key, contents).is_ok());
+    if !written {
+        error!("Failed to write save {}", key);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn delete_save(key: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(key);
+    }
+}
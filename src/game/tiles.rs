@@ -1,17 +1,49 @@
 use super::*;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Intensity {
     Low = 1,
     Medium,
     High,
 }
 
-#[derive(Clone, PartialEq, Eq, Component)]
+#[derive(Clone, Debug, PartialEq, Eq, Component)]
 pub enum Tile {
     Empty,
     Wall,
     Fire(Intensity),
+    /// Refills the player's water when stepped in. Fire dries it out before it can burn.
+    Puddle,
+    /// Bursts straight into a High fire when fire reaches it.
+    Fuel,
+    /// Blocks the player and the fire until a card opens it.
+    Door,
+    /// Burnt-out ground that can't catch fire again.
+    Scorched,
+}
+
+/// How many tiles across the tile sprite sheet is.
+const TILE_SPRITE_COLUMNS: usize = 11;
+
+impl Tile {
+    pub fn sprite_index(&self) -> usize {
+        match self {
+            Tile::Empty => 0,
+            Tile::Fire(Intensity::Low) => 1,
+            Tile::Fire(Intensity::Medium) => 2,
+            Tile::Fire(Intensity::High) => 3,
+            Tile::Wall => 4,
+            Tile::Puddle => 7,
+            Tile::Fuel => 8,
+            Tile::Door => 9,
+            Tile::Scorched => 10,
+        }
+    }
+
+    /// Whether the player and the fire are kept out.
+    pub fn is_blocking(&self) -> bool {
+        matches!(self, Tile::Wall | Tile::Door)
+    }
 }
 
 #[derive(Default)]
@@ -24,18 +56,86 @@ pub enum Spawner<T> {
     Static(Vec<(i32, i32, T)>),
 }
 
+impl<T: Clone> Spawner<T> {
+    /// Where the spawner puts its entities. `Chance(chance, min, max)` places
+    /// `min` of them and then each of the other `max - min` with the given
+    /// chance, every one on a different tile taken from `open`.
+    pub fn place(
+        &self,
+        open: &[(i32, i32)],
+        rng: &mut GameRng,
+        mut pick: impl FnMut(&mut GameRng) -> Option<T>,
+    ) -> Vec<(i32, i32, T)> {
+        match self {
+            Spawner::Static(positions) => positions.clone(),
+            Spawner::Chance(chance, min, max) => {
+                let chance = f64::from(*chance).clamp(0.0, 1.0);
+                let extra = (*min..*max).filter(|_| rng.gen_bool(chance)).count();
+                let count = (*min).max(0) as usize + extra;
+                let mut open = open.to_vec();
+                let mut placed = Vec::new();
+                while placed.len() < count && !open.is_empty() {
+                    let (x, y) = open.swap_remove(rng.gen_range(0..open.len()));
+                    let Some(value) = pick(rng) else {
+                        break;
+                    };
+                    placed.push((x, y, value));
+                }
+                placed
+            }
+        }
+    }
+}
+
 impl<T> Default for Spawner<T> {
     fn default() -> Self {
         Self::Static(vec![])
     }
 }
 
+/// How fire behaves on a map. The default is the classic rule: only High
+/// fires spread, always, and only to orthogonal neighbours.
+#[derive(Clone, Debug)]
+pub struct FireModel {
+    /// The chance a Low, Medium and High fire spreads to each empty neighbour
+    /// at the end of a turn.
+    pub spread_chance: [f32; 3],
+    /// Whether fire also spreads to diagonal neighbours.
+    pub diagonal: bool,
+    /// Fire spreads more easily downwind and less easily upwind.
+    pub wind: Option<GameDirection>,
+    /// High fires burn out after this many turns at High.
+    pub burnout: Option<u32>,
+}
+
+impl Default for FireModel {
+    fn default() -> Self {
+        Self {
+            spread_chance: [0.0, 0.0, 1.0],
+            diagonal: false,
+            wind: None,
+            burnout: None,
+        }
+    }
+}
+
+/// How many turns a High fire has burned at High, for burnout.
+#[derive(Clone, Copy, Component)]
+pub struct TurnsAtHigh(pub u32);
+
 #[derive(Clone, Default, Resource)]
 pub struct MapParameters {
     pub columns: i32,
     pub rows: i32,
+    pub start: GamePosition,
+    pub walls: Vec<(i32, i32)>,
     pub flame_spawner: Spawner<Tile>,
     pub item_spawner: Spawner<Item>,
+    pub fire: FireModel,
+    /// Puddles, fuel, doors and scorched ground, placed before any spawner.
+    pub terrain: Vec<(i32, i32, Tile)>,
+    /// What a puzzle can be finished in, if it has been worked out.
+    pub par: Option<Par>,
 }
 
 pub fn spawn_tiles(mut commands: Commands) {
@@ -44,7 +144,16 @@ pub fn spawn_tiles(mut commands: Commands) {
 
 pub fn despawn_tiles_and_items(
     mut commands: Commands,
-    query: Query<Entity, Or<(With<Tile>, With<Grid>, With<Item>, With<Animation>)>>,
+    query: Query<
+        Entity,
+        Or<(
+            With<Tile>,
+            With<Grid>,
+            With<Item>,
+            With<Animation>,
+            With<ForecastMarker>,
+        )>,
+    >,
 ) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
@@ -52,7 +161,7 @@ pub fn despawn_tiles_and_items(
 }
 
 #[derive(Resource)]
-pub struct TileSpriteSheet(Handle<TextureAtlas>);
+pub struct TileSpriteSheet(pub Handle<TextureAtlas>);
 
 impl FromWorld for TileSpriteSheet {
     fn from_world(world: &mut World) -> Self {
@@ -61,7 +170,14 @@ impl FromWorld for TileSpriteSheet {
             .expect("Failed get the `AssetServer` resource from the `World`");
         let texture_handle = asset_server.load("tiles.png");
         let texture_atlas =
-            TextureAtlas::from_grid(texture_handle, Vec2::new(64.0, 64.0), 7, 1, None, None);
+            TextureAtlas::from_grid(
+                texture_handle,
+                Vec2::new(64.0, 64.0),
+                TILE_SPRITE_COLUMNS,
+                1,
+                None,
+                None,
+            );
         let mut texture_atlases = world
             .get_resource_mut::<Assets<TextureAtlas>>()
             .expect("Failed get the `Assets<TextureAtlas>` resource from the `World`");
@@ -71,49 +187,88 @@ impl FromWorld for TileSpriteSheet {
 }
 
 fn tile_is_wall(x: i32, y: i32, map: &MapParameters) -> bool {
-    x == 0 || y == 0 || x == map.columns + 1 || y == map.rows + 1
-}
-
-fn tile_is_flame(
-    x: i32,
-    y: i32,
-    map: &MapParameters,
-    flame_count: i32,
-    pre_flames: &HashMap<(i32, i32), bool>,
-) -> bool {
-    match &map.flame_spawner {
-        Spawner::Chance(chance, _min_count, max_count) => {
-            if pre_flames.contains_key(&(x, y)) {
-                return true;
-            }
-            if flame_count >= *max_count {
-                return false;
+    x == 0
+        || y == 0
+        || x == map.columns + 1
+        || y == map.rows + 1
+        || map.walls.contains(&(x, y))
+}
+
+/// The tile a map starts with before any spawners run.
+fn tile_at(x: i32, y: i32, map: &MapParameters) -> Tile {
+    if tile_is_wall(x, y, map) {
+        return Tile::Wall;
+    }
+    map.terrain
+        .iter()
+        .find(|(terrain_x, terrain_y, _)| (*terrain_x, *terrain_y) == (x, y))
+        .map(|(_, _, tile)| tile.clone())
+        .unwrap_or(Tile::Empty)
+}
+
+/// Every tile a spawner may use: plain ground and not the start, so the
+/// player never begins a level standing in a fire or on an item.
+fn open_tiles(map: &MapParameters) -> Vec<(i32, i32)> {
+    (1..=map.rows)
+        .flat_map(|y| (1..=map.columns).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            tile_at(x, y, map) == Tile::Empty && (x, y) != (map.start.x, map.start.y)
+        })
+        .collect()
+}
+
+/// Interior walls for a Rogue level. Every layout leaves the start tile clear
+/// and keeps all of the open tiles connected to it.
+pub fn random_layout(
+    columns: i32,
+    rows: i32,
+    start: &GamePosition,
+    rng: &mut GameRng,
+) -> Vec<(i32, i32)> {
+    if columns < 4 || rows < 4 {
+        return Vec::new();
+    }
+    let mut walls = Vec::new();
+    match rng.gen_range(0..4) {
+        // L-shaped room: wall off the quarter furthest from the start
+        1 => {
+            let (width, height) = (columns / 2, rows / 2);
+            let xs = if start.x > columns / 2 { 1..=width } else { columns - width + 1..=columns };
+            let ys = if start.y > rows / 2 { 1..=height } else { rows - height + 1..=rows };
+            for y in ys {
+                for x in xs.clone() {
+                    walls.push((x, y));
+                }
             }
-            let mut rng = rand::thread_rng();
-            rng.gen_bool((1.0 - chance).into())
         }
-        Spawner::Static(positions) => {
-            positions.iter()
-                .map(|(x, y, _)| (*x, *y))
-                .collect::<Vec<(i32, i32)>>()
-                .contains(&(x, y))
+        // Two rooms joined by a doorway
+        2 => {
+            let y = rows / 2 + 1;
+            let gap = rng.gen_range(1..=columns);
+            walls.extend((1..=columns).filter(|x| *x != gap).map(|x| (x, y)));
+        }
+        // Pillars on every other tile
+        3 => {
+            for y in (2..rows).step_by(2) {
+                for x in (2..columns).step_by(2) {
+                    walls.push((x, y));
+                }
+            }
         }
+        _ => {}
     }
+    walls.retain(|&(x, y)| (x, y) != (start.x, start.y));
+    walls
 }
 
-fn random_non_wall_tile(map: &MapParameters) -> (i32, i32) {
-    let mut rng = rand::thread_rng();
-    let x = rng.gen_range(1..=map.columns);
-    // Hack to prevent the flame from spawning on the start tile
-    if x == 1 {
-        (x, rng.gen_range(2..=map.rows))
-    } else {
-        (x, rng.gen_range(1..=map.rows))
+impl bevy::ecs::system::Command for SpawnTiles {
+    fn apply(self, world: &mut World) {
+        world.resource_scope(|world, mut rng: Mut<GameRng>| self.spawn(world, &mut rng));
     }
 }
 
-impl bevy::ecs::system::Command for SpawnTiles {
-    fn apply(self, world: &mut World) {
+impl SpawnTiles {
+    fn spawn(self, world: &mut World, rng: &mut GameRng) {
         let sprite_sheet = world
             .get_resource::<TileSpriteSheet>()
             .expect("Failed get the `TileSpriteSheet` resource from the `World`");
@@ -122,68 +277,46 @@ impl bevy::ecs::system::Command for SpawnTiles {
             .expect("Failed get the `MapParameters` resource from the `World`")
             .clone();
         let atlas = sprite_sheet.0.clone();
-        let mut is_wall;
-        let mut is_flame;
+        let item_table = ItemTable::from_card_infos(
+            world
+                .get_resource::<CardInfoMap>()
+                .expect("Failed get the `CardInfoMap` resource from the `World`"),
+        );
+
+        let mut open = open_tiles(&map);
+        let flames: HashMap<(i32, i32), Tile> = map
+            .flame_spawner
+            .place(&open, rng, |_| Some(Tile::Fire(Intensity::Low)))
+            .into_iter()
+            .map(|(x, y, tile)| ((x, y), tile))
+            .collect();
+        open.retain(|tile| !flames.contains_key(tile));
+        let items: Vec<(Item, GamePosition)> = map
+            .item_spawner
+            .place(&open, rng, |rng| item_table.pick(rng))
+            .into_iter()
+            .filter(|(x, y, _)| !flames.contains_key(&(*x, *y)))
+            .map(|(x, y, item)| (item, GamePosition { x, y, ..default() }))
+            .collect();
+
         let mut entities: Vec<Vec<Entity>> = Vec::new();
-        let mut flame_count = 0;
-        let mut pre_def_flames = HashMap::new();
-        let mut items: Vec<(Item, GamePosition)> = Vec::new();
-        match map.flame_spawner {
-            Spawner::Chance(_, min_count, _) => {
-                for _ in 0..min_count {
-                    // TODO this can hit the same tile more than once
-                    let (x, y) = random_non_wall_tile(&map);
-                    pre_def_flames.insert((x, y), true);
-                }
-            }
-            _ => {}
-        }
         for y in 0..=map.rows + 1 {
             entities.push(Vec::new());
             for x in 0..=map.columns + 1 {
-                is_wall = tile_is_wall(x, y, &map);
-                is_flame = tile_is_flame(x, y, &map, flame_count, &pre_def_flames);
+                let tile = flames
+                    .get(&(x, y))
+                    .cloned()
+                    .unwrap_or_else(|| tile_at(x, y, &map));
+                let index = tile.sprite_index();
+                let is_wall = tile.is_blocking();
                 let mut ec = world.spawn((
                     GamePosition { x, y, ..default() },
                     SpriteSheetBundle {
-                        sprite: TextureAtlasSprite::new(if is_wall {
-                            4
-                        } else {
-                            if is_flame {
-                                1
-                            } else {
-                                0
-                            }
-                        }),
+                        sprite: TextureAtlasSprite::new(index),
                         texture_atlas: atlas.clone(),
                         ..default()
                     },
-                    if is_wall {
-                        Tile::Wall
-                    } else {
-                        if is_flame {
-                            flame_count += 1;
-                            Tile::Fire(Intensity::Low)
-                        } else {
-                            match map.item_spawner {
-                                Spawner::Chance(c, _, _) => {
-                                    let mut rng = rand::thread_rng();
-                                    if rng.gen_bool(c.into()) {
-                                        // TODO the content_range param should check the actual card_infos
-                                        items.push((Item::random(20), GamePosition { x, y, ..default() }));
-                                    }
-                                },
-                                Spawner::Static(ref positions) => {
-                                    for position in positions.iter() {
-                                        if position.0 == x && position.1 == y {
-                                            items.push((position.2.clone(), GamePosition { x, y, ..default() }));
-                                        }
-                                    }
-                                }
-                            }
-                            Tile::Empty
-                        }
-                    },
+                    tile,
                 ));
                 if is_wall {
                     ec.insert(BlockedTile);
@@ -216,22 +349,91 @@ pub fn update_tiles(
                 _ => {}
             }
         }
-        match tile {
-            Tile::Fire(level) => {
-                sprite_index.index = match level {
-                    Intensity::Low => 1,
-                    Intensity::Medium => 2,
-                    Intensity::High => 3,
-                };
-            }
-            Tile::Wall => {
-                sprite_index.index = 4;
-                commands.entity(tile_id).insert(BlockedTile);
-            }
-            _ => {
-                sprite_index.index = 0;
-                commands.entity(tile_id).remove::<BlockedTile>();
-            }
+        sprite_index.index = tile.sprite_index();
+        if tile.is_blocking() {
+            commands.entity(tile_id).insert(BlockedTile);
+        } else {
+            commands.entity(tile_id).remove::<BlockedTile>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(columns: i32, rows: i32) -> Vec<(i32, i32)> {
+        (1..=rows)
+            .flat_map(|y| (1..=columns).map(move |x| (x, y)))
+            .collect()
+    }
+
+    fn is_distinct(placed: &[(i32, i32, u32)]) -> bool {
+        placed
+            .iter()
+            .enumerate()
+            .all(|(i, (x, y, _))| placed[i + 1..].iter().all(|(x2, y2, _)| (x, y) != (x2, y2)))
+    }
+
+    #[test]
+    fn chance_spawner_places_between_min_and_max() {
+        let open = open(4, 4);
+        for seed in 0..20 {
+            let mut rng = GameRng::new(seed);
+            let never = Spawner::Chance(0.0, 2, 5).place(&open, &mut rng, |_| Some(0));
+            assert_eq!(never.len(), 2);
+            let always = Spawner::Chance(1.0, 2, 5).place(&open, &mut rng, |_| Some(0));
+            assert_eq!(always.len(), 5);
+            let some = Spawner::Chance(0.5, 1, 6).place(&open, &mut rng, |_| Some(0));
+            assert!((1..=6).contains(&some.len()), "seed {seed} placed {}", some.len());
+        }
+    }
+
+    #[test]
+    fn chance_spawner_uses_distinct_open_tiles() {
+        let open = vec![(1, 1), (2, 3), (3, 2)];
+        for seed in 0..20 {
+            let mut rng = GameRng::new(seed);
+            let placed = Spawner::Chance(1.0, 5, 5).place(&open, &mut rng, |_| Some(7));
+            // Only as many as there are tiles to put them on
+            assert_eq!(placed.len(), open.len());
+            assert!(is_distinct(&placed));
+            assert!(placed
+                .iter()
+                .all(|(x, y, value)| open.contains(&(*x, *y)) && *value == 7));
+        }
+    }
+
+    #[test]
+    fn chance_spawner_stops_when_nothing_is_picked() {
+        let mut rng = GameRng::new(1);
+        let placed = Spawner::<u32>::Chance(1.0, 3, 3).place(&open(3, 3), &mut rng, |_| None);
+        assert!(placed.is_empty());
+    }
+
+    #[test]
+    fn static_spawner_keeps_its_positions() {
+        let mut rng = GameRng::new(1);
+        let spawner = Spawner::Static(vec![(2, 2, 1), (3, 1, 2)]);
+        assert_eq!(spawner.place(&[], &mut rng, |_| Some(0)), vec![(2, 2, 1), (3, 1, 2)]);
+    }
+
+    #[test]
+    fn random_layouts_stay_inside_and_off_the_start() {
+        let start = GamePosition {
+            x: 2,
+            y: 3,
+            ..default()
+        };
+        for seed in 0..20 {
+            let mut rng = GameRng::new(seed);
+            let walls = random_layout(6, 5, &start, &mut rng);
+            assert!(!walls.contains(&(start.x, start.y)));
+            assert!(walls
+                .iter()
+                .all(|(x, y)| (1..=6).contains(x) && (1..=5).contains(y)));
         }
+        let mut rng = GameRng::new(1);
+        assert!(random_layout(3, 3, &start, &mut rng).is_empty());
     }
 }
@@ -0,0 +1,112 @@
+use super::*;
+
+/// Everything a card play can change, captured just before the play.
+pub struct Snapshot {
+    position: GamePosition,
+    energy: Energy,
+    water: Water,
+    hand: Hand,
+    deck: Deck,
+    deck_list: Vec<DeckCard>,
+    tiles: Vec<(Entity, Tile)>,
+    items: Vec<(Item, GamePosition)>,
+    cards: Vec<Entity>,
+    /// Undoing a play also takes back the random draws it made, so playing it
+    /// again draws the same cards and the fire rolls the same way.
+    rng: GameRng,
+}
+
+/// The plays made this turn, most recent last. Cleared when a turn starts
+/// since the fire's move can't be taken back.
+#[derive(Default, Resource)]
+pub struct UndoStack(pub Vec<Snapshot>);
+
+pub fn clear_undo_stack(mut undo_stack: ResMut<UndoStack>) {
+    undo_stack.0.clear();
+}
+
+pub fn push_undo_snapshot(
+    mut undo_stack: ResMut<UndoStack>,
+    actions: Query<&CardActionType>,
+    (deck_list, rng): (Res<DeckList>, Res<GameRng>),
+    player: Query<(&GamePosition, &Energy, &Water, &Hand, &Deck), With<Player>>,
+    tiles: Query<(Entity, &Tile)>,
+    items: Query<(&Item, &GamePosition)>,
+    cards: Query<Entity, With<ContentID>>,
+) {
+    for action in actions.iter() {
+        if !matches!(action, CardActionType::Play(_)) {
+            continue;
+        }
+        let (position, energy, water, hand, deck) =
+            player.get_single().expect("Should be exactly 1 player");
+        undo_stack.0.push(Snapshot {
+            position: position.clone(),
+            energy: energy.clone(),
+            water: water.clone(),
+            hand: hand.clone(),
+            deck: deck.clone(),
+            deck_list: deck_list.0.clone(),
+            tiles: tiles
+                .iter()
+                .map(|(tile_id, tile)| (tile_id, tile.clone()))
+                .collect(),
+            items: items
+                .iter()
+                .map(|(item, position)| (item.clone(), position.clone()))
+                .collect(),
+            cards: cards.iter().collect(),
+            rng: rng.clone(),
+        });
+    }
+}
+
+pub fn undo_last_play(
+    mut commands: Commands,
+    mut events: EventReader<UndoClicked>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut deck_list: ResMut<DeckList>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    player: Query<Entity, With<Player>>,
+    mut tiles: Query<&mut Tile>,
+    items: Query<Entity, (With<Item>, With<GamePosition>)>,
+    cards: Query<Entity, With<ContentID>>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let Some(snapshot) = undo_stack.0.pop() else {
+        return;
+    };
+    info!("Undoing the last play");
+    commands.remove_resource::<PendingDiscard>();
+    let player_id = player.get_single().expect("Should be exactly 1 player");
+    commands.entity(player_id).insert((
+        snapshot.position,
+        snapshot.energy,
+        snapshot.water,
+        snapshot.hand,
+        snapshot.deck,
+    ));
+    deck_list.0 = snapshot.deck_list;
+    commands.insert_resource(snapshot.rng);
+    for (tile_id, tile) in snapshot.tiles {
+        if let Ok(mut current) = tiles.get_mut(tile_id) {
+            if *current != tile {
+                *current = tile;
+            }
+        }
+    }
+    // Items are despawned when picked up, so put back fresh copies
+    for item_id in items.iter() {
+        commands.entity(item_id).despawn_recursive();
+    }
+    commands.spawn_batch(snapshot.items);
+    // Cards picked up since the snapshot no longer exist
+    for card_id in cards.iter() {
+        if !snapshot.cards.contains(&card_id) {
+            commands.entity(card_id).despawn_recursive();
+        }
+    }
+    next_turn_state.set(TurnState::Started);
+}
@@ -1,9 +1,11 @@
 use bevy::prelude::*;
 use camera::*;
+use editor::*;
 use game::*;
 use ui::*;
 
 mod camera;
+mod editor;
 mod game;
 mod ui;
 
@@ -51,10 +53,17 @@ fn handle_input(
     mut camera_transform: Query<&mut Transform, With<MainCamera>>,
     player_state: Query<(Entity, &GamePosition, &Energy, &Hand), With<Player>>,
     statuses: Query<&CardStatus>,
+    mut undo_clicked: EventWriter<UndoClicked>,
+    playback: Option<Res<Playback>>,
+    pending_discard: Option<Res<PendingDiscard>>,
 ) {
     if keyboard_input.get_just_released().last().is_none() {
         return;
     }
+    // Only leaving the level is allowed while a replay plays itself
+    if playback.is_some() && keyboard_input.get_just_released().last() != Some(&KeyCode::Escape) {
+        return;
+    }
     match keyboard_input.get_just_released().last() {
         Some(KeyCode::Return) => {
             if turn_state.get() != &TurnState::WaitingForInput {
@@ -70,6 +79,12 @@ fn handle_input(
             next_app_state.set(AppState::MainMenu);
             next_game_state.set(GameState::None);
         }
+        Some(KeyCode::Back) => {
+            if turn_state.get() != &TurnState::WaitingForInput {
+                return;
+            }
+            undo_clicked.send(UndoClicked);
+        }
         Some(x) if x < &KeyCode::Key6 => {
             if turn_state.get() != &TurnState::WaitingForInput {
                 return;
@@ -79,6 +94,14 @@ fn handle_input(
                 .expect("Should be exactly 1 player");
             let index = x.clone() as usize - KeyCode::Key1 as usize;
             if let Some(card) = hand.0[index] {
+                if pending_discard.is_some() {
+                    commands.spawn(CardActionType::Discard(Discard {
+                        card,
+                        deck: entity,
+                        hand: entity,
+                    }));
+                    return;
+                }
                 if !statuses
                     .get(card)
                     .expect("Card in hand should have status")
@@ -127,6 +150,8 @@ enum AppState {
     LevelMenu,
     ResultMenu,
     Game,
+    Editor,
+    Shop,
 }
 
 fn handle_main_menu_events(
@@ -135,20 +160,65 @@ fn handle_main_menu_events(
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut game_mode: ResMut<GameMode>,
+    card_infos: Res<CardInfoMap>,
+    puzzles: Res<Puzzles>,
+    seed_entry: Res<SeedEntry>,
+    mut rng: ResMut<GameRng>,
     windows: Query<(Entity, &Window)>,
 ) {
     for event in events.read() {
         match event {
+            MainMenuEvent::ContinuePressed
+            | MainMenuEvent::ReplayPressed
+            | MainMenuEvent::PuzzlePressed
+            | MainMenuEvent::RoguePressed
+            | MainMenuEvent::EditorPressed
+                if card_infos.0.is_empty() =>
+            {
+                warn!("Card definitions have not been loaded yet");
+            }
+            MainMenuEvent::ReplayPressed => {
+                let Some(replay) = Replay::load() else {
+                    warn!("No replay to watch");
+                    continue;
+                };
+                if replay.mode == GameMode::Puzzle && puzzles.is_empty() {
+                    warn!("No puzzles have been loaded");
+                    continue;
+                }
+                *game_mode = replay.mode;
+                commands.insert_resource(Playback::new(replay));
+                app_state.set(AppState::Game);
+                game_state.set(GameState::Loading);
+            }
+            MainMenuEvent::PuzzlePressed if puzzles.is_empty() => {
+                warn!("No puzzles have been loaded");
+            }
+            MainMenuEvent::ContinuePressed => {
+                let Some(save) = RogueSave::load() else {
+                    warn!("No Rogue run to continue");
+                    continue;
+                };
+                commands.insert_resource(ContinueRun(save));
+                app_state.set(AppState::LevelMenu);
+                game_state.set(GameState::Loading);
+                *game_mode = GameMode::Rogue;
+            }
             MainMenuEvent::PuzzlePressed => {
+                *rng = GameRng::new(seed_entry.seed().unwrap_or_else(GameRng::random_seed));
                 app_state.set(AppState::LevelMenu);
                 game_state.set(GameState::Loading);
                 *game_mode = GameMode::Puzzle;
             }
             MainMenuEvent::RoguePressed => {
+                *rng = GameRng::new(seed_entry.seed().unwrap_or_else(GameRng::random_seed));
                 app_state.set(AppState::LevelMenu);
                 game_state.set(GameState::Loading);
                 *game_mode = GameMode::Rogue;
             }
+            MainMenuEvent::EditorPressed => {
+                app_state.set(AppState::Editor);
+            }
             MainMenuEvent::ExitPressed => {
                 for (window_id, window) in windows.iter() {
                     if !window.focused {
@@ -165,6 +235,8 @@ fn handle_level_menu_events(
     mut events: EventReader<LevelMenuEvent>,
     mut app_state: ResMut<NextState<AppState>>,
     mut game_state: ResMut<NextState<GameState>>,
+    current_game_state: Res<State<GameState>>,
+    mut level_index: ResMut<LevelIndex>,
 ) {
     for event in events.read() {
         match event {
@@ -175,6 +247,16 @@ fn handle_level_menu_events(
                 app_state.set(AppState::MainMenu);
                 game_state.set(GameState::None);
             }
+            LevelMenuEvent::LevelPicked(index) => {
+                if level_index.0 as usize == *index {
+                    continue;
+                }
+                level_index.0 = *index as i32;
+                // A level that already loaded is swapped for the picked one
+                if *current_game_state.get() == GameState::Loaded {
+                    game_state.set(GameState::Loading);
+                }
+            }
         }
     }
 }
@@ -201,8 +283,45 @@ fn handle_result_menu_events(
     }
 }
 
+fn handle_shop_menu_events(
+    mut events: EventReader<ShopMenuEvent>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut deck_list: ResMut<DeckList>,
+    card_infos: Res<CardInfoMap>,
+) {
+    for event in events.read() {
+        match event {
+            ShopMenuEvent::TakePressed(content_id) => {
+                deck_list.0.push(content_id.clone().into());
+            }
+            ShopMenuEvent::RemovePressed(card) => {
+                if !remove_from_deck(&mut deck_list, card) {
+                    continue;
+                }
+            }
+            ShopMenuEvent::UpgradePressed(card) => {
+                if !upgrade_in_deck(&mut deck_list, card, &card_infos) {
+                    continue;
+                }
+            }
+            ShopMenuEvent::SkipPressed => {}
+        }
+        app_state.set(AppState::LevelMenu);
+        game_state.set(GameState::Loaded);
+    }
+}
+
+/// Turn limit for `--check-puzzles`.
+#[cfg(not(target_arch = "wasm32"))]
+const SOLVER_MAX_TURNS: u32 = 15;
 
 fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    if std::env::args().any(|arg| arg == "--check-puzzles") {
+        let all_solvable = check_puzzles(SOLVER_MAX_TURNS);
+        std::process::exit(if all_solvable { 0 } else { 1 });
+    }
     App::new()
         .insert_resource(bevy::asset::AssetMetaCheck::Never)
         .add_state::<AppState>()
@@ -211,8 +330,10 @@ fn main() {
         .add_plugins(MenuUIPlugin)
         .add_plugins(LevelUIPlugin)
         .add_plugins(ResultUIPlugin)
+        .add_plugins(ShopUIPlugin)
         .add_plugins(TooltipPlugin)
         .add_plugins(GamePlugin)
+        .add_plugins(EditorPlugin)
         .add_systems(
             Update,
             (handle_main_menu_events).run_if(in_state(AppState::MainMenu)),
@@ -222,12 +343,21 @@ fn main() {
         .add_systems(
             Update,
             (handle_level_menu_events.run_if(in_state(AppState::LevelMenu)),
-                    handle_result_menu_events.run_if(in_state(AppState::ResultMenu)),)
+                    handle_result_menu_events.run_if(in_state(AppState::ResultMenu)),
+                    handle_shop_menu_events.run_if(in_state(AppState::Shop)),)
         )
         .add_systems(OnEnter(AppState::LevelMenu), level_menu::spawn)
         .add_systems(OnExit(AppState::LevelMenu), level_menu::despawn)
+        .add_systems(
+            Update,
+            level_menu::respawn
+                .run_if(in_state(AppState::LevelMenu))
+                .run_if(resource_changed::<LevelIndex>()),
+        )
         .add_systems(OnEnter(AppState::ResultMenu), result_menu::spawn)
         .add_systems(OnExit(AppState::ResultMenu), result_menu::despawn)
+        .add_systems(OnEnter(AppState::Shop), (stock_shop, shop_menu::spawn).chain())
+        .add_systems(OnExit(AppState::Shop), shop_menu::despawn)
         .add_systems(OnEnter(AppState::Game), spawn_game_ui)
         .add_systems(OnExit(AppState::Game), despawn_game_ui)
         .add_systems(
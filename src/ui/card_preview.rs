@@ -0,0 +1,150 @@
+use super::*;
+
+/// A highlight on the map showing what the hovered card would do.
+#[derive(Component)]
+pub struct PreviewMarker;
+
+/// Draws the preview for a card in the hand.
+pub struct SpawnCardPreview {
+    pub card: Entity,
+}
+
+fn same_place(a: &GamePosition, b: &GamePosition) -> bool {
+    (a.x, a.y, a.d.clone() as u8) == (b.x, b.y, b.d.clone() as u8)
+}
+
+/// Redraws the preview when a different card is hovered or the robot moves.
+pub fn update_card_preview(
+    mut commands: Commands,
+    mut shown: Local<Option<(Entity, GamePosition)>>,
+    turn_state: Res<State<TurnState>>,
+    hovered: Query<&CardInstance, With<Hovered>>,
+    player: Query<&GamePosition, With<Player>>,
+    markers: Query<Entity, With<PreviewMarker>>,
+) {
+    let card = hovered.iter().find_map(|card_instance| card_instance.0);
+    let preview = match (turn_state.get(), card, player.get_single()) {
+        (TurnState::WaitingForInput, Some(card), Ok(position)) => Some((card, position.clone())),
+        _ => None,
+    };
+    let same = match (&preview, &*shown) {
+        (Some((card, position)), Some((shown_card, shown_position))) => {
+            card == shown_card && same_place(position, shown_position)
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    if same {
+        return;
+    }
+    for entity in markers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some((card, _)) = &preview {
+        commands.add(SpawnCardPreview { card: *card });
+    }
+    *shown = preview;
+}
+
+fn tile_marker(world: &mut World, position: &GamePosition, color: Color) {
+    world.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(56.0, 56.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 0.6),
+            ..default()
+        },
+        GamePosition {
+            x: position.x,
+            y: position.y,
+            ..default()
+        },
+        PreviewMarker,
+    ));
+}
+
+impl bevy::ecs::system::Command for SpawnCardPreview {
+    fn apply(self, world: &mut World) {
+        let Some((content_id, upgrade)) = world
+            .get::<ContentID>(self.card)
+            .map(|content_id| (content_id.clone(), world.get::<Upgrade>(self.card).cloned()))
+        else {
+            return;
+        };
+        let Some(card_info) = world
+            .get_resource::<CardInfoMap>()
+            .and_then(|card_infos| card_infos.effective(&content_id, upgrade.as_ref()))
+        else {
+            return;
+        };
+        let Ok(position) = world
+            .query_filtered::<&GamePosition, With<Player>>()
+            .get_single(world)
+            .cloned()
+        else {
+            return;
+        };
+        let mut grids = world.query::<&Grid>();
+        let Ok(grid) = grids.get_single(world) else {
+            return;
+        };
+        let board = grid.board(|id| world.get::<Tile>(id).cloned());
+        // Blocked cards can't be played, so there is nothing to preview
+        let Some(resolution) = resolve_effects(&card_info.effects, &position, &board) else {
+            return;
+        };
+        for step in resolution.steps.iter() {
+            match step {
+                EffectStep::Moved(to) => {
+                    tile_marker(world, to, Color::rgba(0.3, 1.0, 0.3, 0.35));
+                }
+                EffectStep::Rotated(_) => {}
+                EffectStep::Sprayed { targets, .. } => {
+                    for target in targets.iter() {
+                        let color = match board.get(target) {
+                            Some(Tile::Fire(_)) => Color::rgba(0.0, 0.7, 1.0, 0.7),
+                            _ => Color::rgba(0.5, 0.8, 1.0, 0.25),
+                        };
+                        tile_marker(world, target, color);
+                    }
+                }
+                EffectStep::WallsPlaced(walls) => {
+                    for wall in walls.iter() {
+                        tile_marker(world, wall, Color::rgba(0.6, 0.6, 0.6, 0.5));
+                    }
+                }
+                EffectStep::DoorsOpened(doors) => {
+                    for door in doors.iter() {
+                        tile_marker(world, door, Color::rgba(1.0, 0.9, 0.3, 0.5));
+                    }
+                }
+            }
+        }
+        // A faded robot where the card leaves the player, facing the new way
+        let end = resolution.position;
+        if same_place(&end, &position) {
+            return;
+        }
+        let atlas = world
+            .get_resource::<PlayerSpriteSheet>()
+            .expect("Failed get the `PlayerSpriteSheet` resource from the `World`")
+            .0
+            .clone();
+        world.spawn((
+            SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                    ..TextureAtlasSprite::new(0)
+                },
+                texture_atlas: atlas,
+                transform: Transform::from_xyz(0.0, 0.0, 0.7),
+                ..default()
+            },
+            end,
+            PreviewMarker,
+        ));
+    }
+}
@@ -23,6 +23,14 @@ pub struct DiscardedUIText;
 #[derive(Component)]
 pub struct CardTitle;
 
+/// Asks the player to pick cards while a discard is pending.
+#[derive(Component)]
+pub struct DiscardPrompt;
+
+/// The wind and whether the fire forecast is shown.
+#[derive(Component)]
+pub struct ForecastUI;
+
 #[derive(Component)]
 pub struct EnergyText;
 
@@ -37,6 +45,9 @@ pub struct CardClicked {
 #[derive(Event)]
 pub struct EndTurnClicked;
 
+#[derive(Event)]
+pub struct UndoClicked;
+
 pub(in crate::ui) const CARD_WIDTH: f32 = 140.0;
 pub(in crate::ui) const CARD_HEIGHT: f32 = 200.0;
 
@@ -47,20 +58,23 @@ pub fn handle_click(
         &RelativeCursorPosition,
         Option<&CardInstance>,
         Option<&Button>,
+        Option<&UndoButton>,
     )>,
     mut down_on_entity: Local<Option<Entity>>,
     mut card_clicked: EventWriter<CardClicked>,
     mut end_turn_clicked: EventWriter<EndTurnClicked>,
+    mut undo_clicked: EventWriter<UndoClicked>,
+    playback: Option<Res<Playback>>,
 ) {
     if button_input.just_pressed(MouseButton::Left) {
-        for (entity, cursor_position, _, _) in cursor_positions.iter() {
+        for (entity, cursor_position, _, _, _) in cursor_positions.iter() {
             if cursor_position.mouse_over() {
                 *down_on_entity = Some(entity);
             }
         }
     }
     if button_input.just_released(MouseButton::Left) {
-        for (entity, cursor_position, card, button) in cursor_positions.iter() {
+        for (entity, cursor_position, card, button, undo) in cursor_positions.iter() {
             if cursor_position.mouse_over() {
                 if down_on_entity.as_ref().is_some() && entity == *down_on_entity.as_ref().unwrap()
                 {
@@ -69,7 +83,12 @@ pub fn handle_click(
                             card_instance: card_instance.clone(),
                         });
                     }
-                    if let Some(_) = button {
+                    if undo.is_some() {
+                        // A replay sends its own recorded undos
+                        if playback.is_none() {
+                            undo_clicked.send(UndoClicked);
+                        }
+                    } else if let Some(_) = button {
                         end_turn_clicked.send(EndTurnClicked);
                     }
                 }
@@ -145,13 +164,21 @@ impl bevy::ecs::system::Command for SpawnHandUI {
                             Interaction::default(),
                             Tooltip {
                                 text: match i {
-                                    2 => "Number of cards in Deck".to_string(),
-                                    3 => "Number of cards that will be Recycled".to_string(),
-                                    4 => "Number oc cards that have been Discarded".to_string(),
+                                    2 => "Number of cards in Deck, click to view".to_string(),
+                                    3 => "Number of cards that will be Recycled, click to view"
+                                        .to_string(),
+                                    4 => "Number oc cards that have been Discarded, click to view"
+                                        .to_string(),
                                     _ => panic!("Invalid icon index"),
                                 },
                                 threshold: 0.0,
                             },
+                            match i {
+                                2 => Pile::Deck,
+                                3 => Pile::Recycled,
+                                4 => Pile::Discarded,
+                                _ => panic!("Invalid icon index"),
+                            },
                         ))
                         .with_children(|icon_container| {
                             icon_container.spawn((AtlasImageBundle {
@@ -402,10 +429,49 @@ pub fn update_discarded_ui(
         .value = format!("{}", deck.discarded.len());
 }
 
+pub fn update_discard_prompt(
+    pending_discard: Option<Res<PendingDiscard>>,
+    mut prompt: Query<(&mut Text, &mut Visibility), With<DiscardPrompt>>,
+) {
+    let Ok((mut text, mut visibility)) = prompt.get_single_mut() else {
+        return;
+    };
+    match pending_discard {
+        Some(pending) => {
+            text.sections[0].value = format!(
+                "Choose {} card{} to discard",
+                pending.0,
+                if pending.0 == 1 { "" } else { "s" }
+            );
+            *visibility = Visibility::Visible;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+pub fn update_forecast_ui(
+    show: Res<ShowForecast>,
+    map: Res<MapParameters>,
+    mut texts: Query<(Ref<ForecastUI>, &mut Text)>,
+) {
+    let Ok((forecast_ui, mut text)) = texts.get_single_mut() else {
+        return;
+    };
+    if !forecast_ui.is_added() && !show.is_changed() && !map.is_changed() {
+        return;
+    }
+    let wind = match &map.fire.wind {
+        Some(direction) => format!("Wind: {:?}", direction),
+        None => "No wind".to_string(),
+    };
+    let forecast = if show.0 { "on" } else { "off" };
+    text.sections[0].value = format!("{}\nForecast (F): {}", wind, forecast);
+}
+
 pub fn update_hand_title_texts(
     hands: Query<&Hand, Or<(Changed<Hand>, Changed<GamePosition>)>>,
     card_info: Res<CardInfoMap>,
-    base_card_info: Query<&ContentID>,
+    base_card_info: Query<(&ContentID, Option<&Upgrade>)>,
     mut card_texts: Query<(&CardUISlot, &mut Text), With<CardTitle>>,
 ) {
     if hands.is_empty() {
@@ -417,10 +483,12 @@ pub fn update_hand_title_texts(
     for (slot, mut text) in card_texts.iter_mut() {
         match hand.0[slot.0] {
             Some(card_instance_id) => {
-                let base_card_id = base_card_info
+                let (base_card_id, upgrade) = base_card_info
                     .get(card_instance_id)
                     .expect("Card without base card info");
-                let card_info = card_info.0.get(&*base_card_id).expect("Card without info");
+                let card_info = card_info
+                    .effective(base_card_id, upgrade)
+                    .expect("Card without info");
                 text.sections[0].value = card_info.name.clone();
             }
             None => {
@@ -433,7 +501,7 @@ pub fn update_hand_title_texts(
 pub fn update_hand_energy_texts(
     hands: Query<&Hand, Or<(Changed<Hand>, Changed<GamePosition>)>>,
     card_info: Res<CardInfoMap>,
-    base_card_info: Query<&ContentID>,
+    base_card_info: Query<(&ContentID, Option<&Upgrade>)>,
     mut card_texts: Query<(&CardUISlot, &mut Text), With<EnergyText>>,
 ) {
     if hands.is_empty() {
@@ -445,10 +513,12 @@ pub fn update_hand_energy_texts(
     for (slot, mut text) in card_texts.iter_mut() {
         match hand.0[slot.0] {
             Some(card_instance_id) => {
-                let base_card_id = base_card_info
+                let (base_card_id, upgrade) = base_card_info
                     .get(card_instance_id)
                     .expect("Card without base card info");
-                let card_info = card_info.0.get(&*base_card_id).expect("Card without info");
+                let card_info = card_info
+                    .effective(base_card_id, upgrade)
+                    .expect("Card without info");
                 let energy_cost = card_info.resource_cost.energy;
                 if energy_cost > 0 {
                     text.sections[0].value = energy_cost.to_string();
@@ -466,7 +536,7 @@ pub fn update_hand_energy_texts(
 pub fn update_hand_water_texts(
     hands: Query<&Hand, Or<(Changed<Hand>, Changed<GamePosition>)>>,
     card_info: Res<CardInfoMap>,
-    base_card_info: Query<&ContentID>,
+    base_card_info: Query<(&ContentID, Option<&Upgrade>)>,
     mut card_texts: Query<(&CardUISlot, &mut Text), With<WaterText>>,
 ) {
     if hands.is_empty() {
@@ -478,10 +548,12 @@ pub fn update_hand_water_texts(
     for (slot, mut text) in card_texts.iter_mut() {
         match hand.0[slot.0] {
             Some(card_instance_id) => {
-                let base_card_id = base_card_info
+                let (base_card_id, upgrade) = base_card_info
                     .get(card_instance_id)
                     .expect("Card without base card info");
-                let card_info = card_info.0.get(&*base_card_id).expect("Card without info");
+                let card_info = card_info
+                    .effective(base_card_id, upgrade)
+                    .expect("Card without info");
                 let water_cost = card_info.resource_cost.water;
                 if water_cost > 0 {
                     text.sections[0].value = water_cost.to_string();
@@ -515,7 +587,7 @@ pub fn update_hand_images(
     hands: Query<(&Hand, &GamePosition), Or<(Changed<Hand>, Changed<GamePosition>)>>,
     card_sprites: Res<CardSpriteSheet>,
     card_info: Res<CardInfoMap>,
-    base_card_info: Query<&ContentID>,
+    base_card_info: Query<(&ContentID, Option<&Upgrade>)>,
     mut card_images: Query<(
         &CardUISlot,
         &mut BackgroundColor,
@@ -533,10 +605,12 @@ pub fn update_hand_images(
     for (slot, mut background, mut transform, mut atlas, mut image) in card_images.iter_mut() {
         match hand.0[slot.0] {
             Some(card_instance_id) => {
-                let base_card_id = base_card_info
+                let (base_card_id, upgrade) = base_card_info
                     .get(card_instance_id)
                     .expect("Card without base card info");
-                let card_info = card_info.0.get(&*base_card_id).expect("Card without info");
+                let card_info = card_info
+                    .effective(base_card_id, upgrade)
+                    .expect("Card without info");
                 background.0 = Color::WHITE.into();
                 *atlas = card_sprites.0.clone();
                 image.index = card_info.texture_index;
@@ -9,14 +9,19 @@ pub struct LevelMenu;
 pub enum LevelMenuOption {
     Play,
     Back,
+    Select(usize),
 }
 
 #[derive(Event)]
 pub enum LevelMenuEvent {
     PlayPressed,
     BackPressed,
+    LevelPicked(usize),
 }
 
+/// Size of one tile in a puzzle thumbnail.
+const THUMBNAIL_TILE: f32 = 8.0;
+
 pub fn despawn(mut commands: Commands, menu_query: Query<Entity, With<LevelMenu>>) {
     for entity in menu_query.iter() {
         commands.entity(entity).despawn_recursive();
@@ -27,6 +32,14 @@ pub fn spawn(mut commands: Commands) {
     commands.add(SpawnMenuUI);
 }
 
+/// Rebuilds the menu after another level is picked.
+pub fn respawn(mut commands: Commands, menu_query: Query<Entity, With<LevelMenu>>) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.add(SpawnMenuUI);
+}
+
 pub fn handle_interactions(
     mut events: EventWriter<LevelMenuEvent>,
     interaction_query: Query<
@@ -42,18 +55,209 @@ pub fn handle_interactions(
             (Interaction::Pressed, LevelMenuOption::Back) => {
                 events.send(LevelMenuEvent::BackPressed);
             }
+            (Interaction::Pressed, LevelMenuOption::Select(index)) => {
+                events.send(LevelMenuEvent::LevelPicked(*index));
+            }
             _ => {}
         }
     }
 }
 
+/// A puzzle in the level select grid.
+struct PuzzleEntry {
+    /// The puzzle's index in the loaded `Puzzles` list, which `Puzzles::get`
+    /// wraps round the list's length. Puzzles that aren't loaded get no entry,
+    /// so this can be ahead of the entry's place in the grid.
+    index: usize,
+    level: Level,
+    unlocked: bool,
+    stars: Option<u32>,
+}
+
+fn thumbnail_color(level: &Level, x: i32, y: i32) -> Color {
+    let at = |(tile_x, tile_y): (i32, i32)| (tile_x, tile_y) == (x, y);
+    if at((level.start.x, level.start.y)) {
+        return Color::WHITE;
+    }
+    if level.walls.iter().any(|wall| at(*wall)) {
+        return Color::DARK_GRAY;
+    }
+    if let Some((_, _, intensity)) = level.flames.iter().find(|(fx, fy, _)| at((*fx, *fy))) {
+        return match intensity {
+            Intensity::Low => Color::ORANGE,
+            Intensity::Medium => Color::ORANGE_RED,
+            Intensity::High => Color::RED,
+        };
+    }
+    if let Some((_, _, tile)) = level.terrain.iter().find(|(tx, ty, _)| at((*tx, *ty))) {
+        return match tile {
+            Tile::Puddle => Color::rgb(0.2, 0.5, 1.0),
+            Tile::Fuel => Color::rgb(0.4, 0.25, 0.1),
+            Tile::Door => Color::rgb(0.6, 0.4, 0.2),
+            _ => Color::rgb(0.2, 0.2, 0.2),
+        };
+    }
+    if level.items.iter().any(|(ix, iy, _)| at((*ix, *iy))) {
+        return Color::YELLOW;
+    }
+    Color::rgb(0.35, 0.35, 0.35)
+}
+
+/// A tiny picture of the puzzle's layout, top row first.
+fn spawn_thumbnail(parent: &mut WorldChildBuilder, level: &Level) {
+    let (columns, rows) = level.map_size;
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|thumbnail| {
+            for y in (1..=rows).rev() {
+                thumbnail
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for x in 1..=columns {
+                            row.spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(THUMBNAIL_TILE),
+                                    height: Val::Px(THUMBNAIL_TILE),
+                                    ..default()
+                                },
+                                background_color: thumbnail_color(level, x, y).into(),
+                                ..default()
+                            });
+                        }
+                    });
+            }
+        });
+}
+
+fn spawn_puzzle_grid(parent: &mut WorldChildBuilder, entries: &[PuzzleEntry], selected: usize) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(8.0),
+                row_gap: Val::Px(8.0),
+                max_width: Val::Vw(80.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|grid| {
+            for entry in entries.iter() {
+                let mut button = grid.spawn(ButtonBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::SpaceBetween,
+                        row_gap: Val::Px(4.0),
+                        width: Val::Px(120.0),
+                        min_height: Val::Px(140.0),
+                        padding: UiRect::all(Val::Px(6.0)),
+                        ..default()
+                    },
+                    background_color: if entry.index == selected {
+                        Color::TEAL.into()
+                    } else if entry.unlocked {
+                        Color::DARK_GRAY.into()
+                    } else {
+                        Color::rgb(0.15, 0.15, 0.15).into()
+                    },
+                    ..default()
+                });
+                if entry.unlocked {
+                    button.insert(LevelMenuOption::Select(entry.index));
+                }
+                button.with_children(|button| {
+                    button.spawn((TextBundle::from_section(
+                        format!("{}. {}", entry.index + 1, entry.level.name),
+                        TextStyle {
+                            font_size: 16.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),));
+                    if entry.unlocked {
+                        spawn_thumbnail(button, &entry.level);
+                    }
+                    button.spawn((TextBundle::from_section(
+                        match (entry.unlocked, entry.stars) {
+                            (false, _) => "Locked".to_string(),
+                            (true, Some(stars)) => format!("{} / 3 stars", stars),
+                            (true, None) => "Not completed".to_string(),
+                        },
+                        TextStyle {
+                            font_size: 16.0,
+                            color: Color::GRAY,
+                            ..default()
+                        },
+                    ),));
+                });
+            }
+        });
+}
+
 impl bevy::ecs::system::Command for SpawnMenuUI {
     fn apply(self, world: &mut World) {
         let level_index = world.get_resource::<LevelIndex>().unwrap().0;
         let game_mode = world.get_resource::<GameMode>().unwrap().clone();
-        let level_index = match game_mode {
-            GameMode::Puzzle => (level_index as usize) % NUM_PUZZLES,
-            GameMode::Rogue => level_index as usize,
+        let profile = world.get_resource::<Profile>().unwrap();
+        let mut entries: Vec<PuzzleEntry> = Vec::new();
+        if game_mode == GameMode::Puzzle {
+            let puzzles = world.get_resource::<Puzzles>().unwrap();
+            let levels = world.get_resource::<Assets<Level>>().unwrap();
+            for index in 0..puzzles.len() {
+                let Some(level) = puzzles.get(index, levels) else {
+                    continue;
+                };
+                entries.push(PuzzleEntry {
+                    index,
+                    level: level.clone(),
+                    unlocked: profile.is_unlocked(index),
+                    stars: profile.record(puzzles, index).map(|record| record.best_stars),
+                });
+            }
+        }
+        let (level_index, level_name, progress) = match game_mode {
+            GameMode::Puzzle => {
+                let puzzles = world.get_resource::<Puzzles>().unwrap();
+                let levels = world.get_resource::<Assets<Level>>().unwrap();
+                let progress = match profile.record(puzzles, level_index as usize) {
+                    Some(record) => format!(
+                        "Completed: {} / 3 stars, best score {} in {} turn(s)",
+                        record.best_stars, record.best_score, record.best_turns
+                    ),
+                    None => "Not completed yet".to_string(),
+                };
+                (
+                    (level_index as usize) % puzzles.len().max(1),
+                    puzzles
+                        .get(level_index as usize, levels)
+                        .map(|level| level.name.clone())
+                        .unwrap_or_default(),
+                    progress,
+                )
+            }
+            GameMode::Rogue => (
+                level_index as usize,
+                String::new(),
+                match profile.rogue_best_depth {
+                    0 => String::new(),
+                    depth => format!("Deepest run: level {}", depth),
+                },
+            ),
         };
         world
             .spawn((
@@ -81,6 +285,29 @@ impl bevy::ecs::system::Command for SpawnMenuUI {
                         ..default()
                     },
                 ),));
+                if !level_name.is_empty() {
+                    parent.spawn((TextBundle::from_section(
+                        level_name,
+                        TextStyle {
+                            font_size: 40.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),));
+                }
+                if !entries.is_empty() {
+                    spawn_puzzle_grid(parent, &entries, level_index);
+                }
+                if !progress.is_empty() {
+                    parent.spawn((TextBundle::from_section(
+                        progress,
+                        TextStyle {
+                            font_size: 28.0,
+                            color: Color::GRAY,
+                            ..default()
+                        },
+                    ),));
+                }
                 parent
                     .spawn((
                         ButtonBundle {
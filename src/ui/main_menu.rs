@@ -7,17 +7,36 @@ struct SpawnMenuUI;
 #[derive(Component)]
 pub struct MainMenu;
 
+#[derive(Component)]
+pub struct SeedText;
+
+/// The seed typed on the main menu. An empty entry starts a run with a random seed.
+#[derive(Default, Resource)]
+pub struct SeedEntry(pub String);
+
+impl SeedEntry {
+    pub fn seed(&self) -> Option<u64> {
+        self.0.parse().ok()
+    }
+}
+
 #[derive(Component, Debug)]
 pub enum MainMenuOption {
+    Continue,
+    Replay,
     Puzzle,
     Rogue,
+    Editor,
     Exit,
 }
 
 #[derive(Event)]
 pub enum MainMenuEvent {
+    ContinuePressed,
+    ReplayPressed,
     PuzzlePressed,
     RoguePressed,
+    EditorPressed,
     ExitPressed,
 }
 
@@ -37,12 +56,21 @@ pub fn handle_interactions(
 ) {
     for (interaction, option) in interaction_query.iter() {
         match (*interaction, option) {
+            (Interaction::Pressed, MainMenuOption::Continue) => {
+                events.send(MainMenuEvent::ContinuePressed);
+            }
+            (Interaction::Pressed, MainMenuOption::Replay) => {
+                events.send(MainMenuEvent::ReplayPressed);
+            }
             (Interaction::Pressed, MainMenuOption::Puzzle) => {
                 events.send(MainMenuEvent::PuzzlePressed);
             }
             (Interaction::Pressed, MainMenuOption::Rogue) => {
                 events.send(MainMenuEvent::RoguePressed);
             }
+            (Interaction::Pressed, MainMenuOption::Editor) => {
+                events.send(MainMenuEvent::EditorPressed);
+            }
             (Interaction::Pressed, MainMenuOption::Exit) => {
                 events.send(MainMenuEvent::ExitPressed);
             }
@@ -51,8 +79,45 @@ pub fn handle_interactions(
     }
 }
 
+pub fn handle_seed_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut seed_entry: ResMut<SeedEntry>,
+    menu: Query<Entity, With<MainMenu>>,
+) {
+    if menu.is_empty() {
+        characters.clear();
+        return;
+    }
+    for event in characters.read() {
+        if event.char.is_ascii_digit() && seed_entry.0.len() < 19 {
+            seed_entry.0.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        seed_entry.0.pop();
+    }
+}
+
+pub fn update_seed_text(
+    seed_entry: Res<SeedEntry>,
+    mut texts: Query<&mut Text, With<SeedText>>,
+) {
+    for mut text in texts.iter_mut() {
+        if seed_entry.is_changed() || text.sections[0].value.is_empty() {
+            text.sections[0].value = if seed_entry.0.is_empty() {
+                "Seed: random".to_string()
+            } else {
+                format!("Seed: {}", seed_entry.0)
+            };
+        }
+    }
+}
+
 impl bevy::ecs::system::Command for SpawnMenuUI {
     fn apply(self, world: &mut World) {
+        let has_saved_run = RogueSave::exists();
+        let has_replay = Replay::exists();
         world
             .spawn((
                 NodeBundle {
@@ -79,6 +144,87 @@ impl bevy::ecs::system::Command for SpawnMenuUI {
                         ..default()
                     },
                 ),));
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 32.0,
+                            color: Color::GRAY,
+                            ..default()
+                        },
+                    ),
+                    Interaction::default(),
+                    Tooltip {
+                        text: "Type a number to replay a run with the same seed".to_string(),
+                        threshold: 0.5,
+                    },
+                    SeedText,
+                ));
+                if has_saved_run {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
+                                    justify_content: JustifyContent::Center,
+                                    min_height: Val::Px(64.0),
+                                    min_width: Val::Vw(30.0),
+                                    ..default()
+                                },
+                                background_color: Color::DARK_GREEN.into(),
+                                ..default()
+                            },
+                            RelativeCursorPosition::default(),
+                            Tooltip {
+                                text: "Resume your Rogue run from the start of its current level"
+                                    .to_string(),
+                                threshold: 0.5,
+                            },
+                            MainMenuOption::Continue,
+                        ))
+                        .with_children(|button| {
+                            button.spawn((TextBundle::from_section(
+                                "Continue",
+                                TextStyle {
+                                    font_size: 40.0,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            ),));
+                        });
+                }
+                if has_replay {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
+                                    justify_content: JustifyContent::Center,
+                                    min_height: Val::Px(64.0),
+                                    min_width: Val::Vw(30.0),
+                                    ..default()
+                                },
+                                background_color: Color::DARK_GRAY.into(),
+                                ..default()
+                            },
+                            RelativeCursorPosition::default(),
+                            Tooltip {
+                                text: "Watch the last level you played".to_string(),
+                                threshold: 0.5,
+                            },
+                            MainMenuOption::Replay,
+                        ))
+                        .with_children(|button| {
+                            button.spawn((TextBundle::from_section(
+                                "Watch Replay",
+                                TextStyle {
+                                    font_size: 40.0,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            ),));
+                        });
+                }
                 parent
                     .spawn((
                         ButtonBundle {
@@ -140,6 +286,36 @@ impl bevy::ecs::system::Command for SpawnMenuUI {
                             },
                         ),));
                     });
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                min_height: Val::Px(64.0),
+                                min_width: Val::Vw(30.0),
+                                ..default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                        RelativeCursorPosition::default(),
+                        Tooltip {
+                            text: "Build your own puzzles and play-test them".to_string(),
+                            threshold: 0.5,
+                        },
+                        MainMenuOption::Editor,
+                    ))
+                    .with_children(|button| {
+                        button.spawn((TextBundle::from_section(
+                            "Level Editor",
+                            TextStyle {
+                                font_size: 40.0,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),));
+                    });
                 parent
                     .spawn((
                         ButtonBundle {
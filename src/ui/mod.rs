@@ -1,17 +1,23 @@
 use super::*;
 
+pub use card_preview::*;
 pub use hand::*;
 pub use level_menu::*;
 pub use main_menu::*;
+pub use pile_viewer::*;
 pub use result_menu::*;
 pub use resource::*;
+pub use shop_menu::*;
 pub use tooltip::*;
 
+pub mod card_preview;
 pub mod hand;
 pub mod level_menu;
 pub mod main_menu;
+pub mod pile_viewer;
 pub mod result_menu;
 pub mod resource;
+pub mod shop_menu;
 pub mod tooltip;
 
 pub struct GameUIPlugin;
@@ -24,9 +30,22 @@ pub struct LevelUIPlugin;
 
 pub struct ResultUIPlugin;
 
+pub struct ShopUIPlugin;
+
+#[derive(Component)]
+pub struct SeedUI;
+
 pub fn despawn_game_ui(
     mut commands: Commands,
-    game_ui: Query<Entity, Or<(With<HandUI>, With<ResourceUI>)>>,
+    game_ui: Query<Entity, Or<(
+        With<HandUI>,
+        With<ResourceUI>,
+        With<SeedUI>,
+        With<DiscardPrompt>,
+        With<ForecastUI>,
+        With<PreviewMarker>,
+        With<PileViewer>,
+    )>>,
 ) {
     for entity in game_ui.iter() {
         commands.entity(entity).despawn_recursive();
@@ -37,8 +56,61 @@ pub fn spawn_game_ui(
     mut commands: Commands,
     player_sprite_sheet: Res<PlayerSpriteSheet>,
     players: Query<Entity, With<Player>>,
+    rng: Res<GameRng>,
 ) {
     commands.add(SpawnHandUI::default());
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Seed: {}", rng.seed),
+            TextStyle {
+                font_size: 20.0,
+                color: Color::GRAY,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        }),
+        SeedUI,
+    ));
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 32.0,
+                color: Color::YELLOW_GREEN,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            left: Val::Px(8.0),
+            ..default()
+        }),
+        DiscardPrompt,
+    ));
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::GRAY,
+                ..default()
+            },
+        )
+        .with_text_alignment(TextAlignment::Right)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        }),
+        ForecastUI,
+    ));
     for player_id in players.iter() {
         commands.entity(player_id).insert(SpriteSheetBundle {
             transform: Transform::from_xyz(0.0, 0.0, 1.0),
@@ -53,6 +125,7 @@ impl Plugin for GameUIPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<CardClicked>()
             .add_event::<EndTurnClicked>()
+            .add_event::<UndoClicked>()
             .add_systems(
                 Update,
                 (
@@ -67,6 +140,12 @@ impl Plugin for GameUIPlugin {
                     update_hand_water_texts,
                     update_interactions,
                     update_playable_indicator,
+                    update_undo_button,
+                    update_discard_prompt,
+                    update_forecast_ui,
+                    update_card_preview,
+                    toggle_pile_viewer,
+                    refresh_pile_viewer,
                     handle_click.run_if(in_state(TurnState::WaitingForInput)),
                 )
                     .run_if(in_state(GameState::Playing)),
@@ -92,7 +171,15 @@ impl Plugin for TooltipPlugin {
 impl Plugin for MenuUIPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<MainMenuEvent>()
-            .add_systems(Update, main_menu::handle_interactions);
+            .init_resource::<SeedEntry>()
+            .add_systems(
+                Update,
+                (
+                    main_menu::handle_interactions,
+                    main_menu::handle_seed_input,
+                    main_menu::update_seed_text,
+                ),
+            );
     }
 }
 
@@ -109,3 +196,10 @@ impl Plugin for ResultUIPlugin {
             .add_systems(Update, result_menu::handle_interactions);
     }
 }
+
+impl Plugin for ShopUIPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShopMenuEvent>()
+            .add_systems(Update, shop_menu::handle_interactions);
+    }
+}
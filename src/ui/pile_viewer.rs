@@ -0,0 +1,189 @@
+use super::*;
+
+/// One of the player's card piles, shown in the deck dock.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Eq)]
+pub enum Pile {
+    Deck,
+    Recycled,
+    Discarded,
+}
+
+impl Pile {
+    fn title(&self) -> &'static str {
+        match self {
+            Pile::Deck => "Deck",
+            Pile::Recycled => "Recycled",
+            Pile::Discarded => "Discarded",
+        }
+    }
+
+    fn cards<'a>(&self, deck: &'a Deck) -> &'a [Entity] {
+        match self {
+            Pile::Deck => &deck.cards,
+            Pile::Recycled => &deck.recycled,
+            Pile::Discarded => &deck.discarded,
+        }
+    }
+}
+
+/// The overlay listing the cards in a pile.
+#[derive(Component)]
+pub struct PileViewer(pub Pile);
+
+pub struct SpawnPileViewer(pub Pile);
+
+/// Opens a pile when its dock icon is clicked, and closes it when the icon
+/// or the overlay is clicked again.
+pub fn toggle_pile_viewer(
+    mut commands: Commands,
+    icons: Query<(&Interaction, &Pile), Changed<Interaction>>,
+    viewers: Query<(Entity, &PileViewer, &Interaction)>,
+) {
+    let clicked = icons
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, pile)| *pile);
+    let closed = viewers
+        .iter()
+        .any(|(_, _, interaction)| *interaction == Interaction::Pressed);
+    if clicked.is_none() && !closed {
+        return;
+    }
+    let mut open = None;
+    for (entity, viewer, _) in viewers.iter() {
+        open = Some(viewer.0);
+        commands.entity(entity).despawn_recursive();
+    }
+    match clicked {
+        Some(pile) if open != Some(pile) => commands.add(SpawnPileViewer(pile)),
+        _ => {}
+    }
+}
+
+/// Keeps an open pile in step with the deck as cards move around.
+pub fn refresh_pile_viewer(
+    mut commands: Commands,
+    changed_decks: Query<(), Changed<Deck>>,
+    viewers: Query<(Entity, &PileViewer)>,
+) {
+    if changed_decks.is_empty() {
+        return;
+    }
+    for (entity, viewer) in viewers.iter() {
+        commands.entity(entity).despawn_recursive();
+        commands.add(SpawnPileViewer(viewer.0));
+    }
+}
+
+impl bevy::ecs::system::Command for SpawnPileViewer {
+    fn apply(self, world: &mut World) {
+        let Ok(deck) = world
+            .query_filtered::<&Deck, With<Player>>()
+            .get_single(world)
+        else {
+            return;
+        };
+        let card_infos = world.get_resource::<CardInfoMap>().unwrap();
+        let mut cards: Vec<(CardInfo, u32)> = self
+            .0
+            .cards(deck)
+            .iter()
+            .filter_map(|card| {
+                let content_id = world.get::<ContentID>(*card)?;
+                let upgrade = world.get::<Upgrade>(*card);
+                let card_info = card_infos.effective(content_id, upgrade)?;
+                Some((card_info, upgrade.map_or(0, |upgrade| upgrade.0)))
+            })
+            .collect();
+        // Sorted so the draw order of the deck isn't given away
+        cards.sort_by(|(a, a_upgrade), (b, b_upgrade)| {
+            (&a.name, a_upgrade).cmp(&(&b.name, b_upgrade))
+        });
+        let atlas = world.get_resource::<CardSpriteSheet>().unwrap().0.clone();
+        world
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(16.0),
+                        top: Val::Px(48.0),
+                        left: Val::Percent(10.0),
+                        width: Val::Percent(80.0),
+                        bottom: Val::Px(CARD_HEIGHT + 32.0),
+                        padding: UiRect::all(Val::Px(16.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                    ..default()
+                },
+                PileViewer(self.0),
+            ))
+            .with_children(|parent| {
+                parent.spawn((TextBundle::from_section(
+                    format!("{} ({})  click to close", self.0.title(), cards.len()),
+                    TextStyle {
+                        font_size: 32.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),));
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            flex_wrap: FlexWrap::Wrap,
+                            justify_content: JustifyContent::Center,
+                            column_gap: Val::Px(8.0),
+                            row_gap: Val::Px(8.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for (card_info, _) in cards.iter() {
+                            row.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Column,
+                                        align_items: AlignItems::Center,
+                                        width: Val::Px(96.0),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                Interaction::default(),
+                                Tooltip {
+                                    text: card_info.full_description(),
+                                    threshold: 0.3,
+                                },
+                            ))
+                            .with_children(|card| {
+                                card.spawn((AtlasImageBundle {
+                                    style: Style {
+                                        width: Val::Px(80.0),
+                                        height: Val::Px(80.0),
+                                        ..default()
+                                    },
+                                    texture_atlas: atlas.clone(),
+                                    texture_atlas_image: UiTextureAtlasImage {
+                                        index: card_info.texture_index,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },));
+                                card.spawn((TextBundle::from_section(
+                                    card_info.name.clone(),
+                                    TextStyle {
+                                        font_size: 16.0,
+                                        color: Color::WHITE,
+                                        ..default()
+                                    },
+                                ),));
+                            });
+                        }
+                    });
+            });
+    }
+}
@@ -19,6 +19,9 @@ pub struct IconSpriteSheet(pub Handle<TextureAtlas>);
 #[derive(Component)]
 pub struct EndTurnButton;
 
+#[derive(Component)]
+pub struct UndoButton;
+
 impl FromWorld for IconSpriteSheet {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world
@@ -91,6 +94,39 @@ pub fn spawn_resource_ui(world: &mut World) -> Entity {
             ),));
         })
         .set_parent(container_id);
+    world
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(28.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                border_color: Color::GRAY.into(),
+                background_color: Color::NONE.into(),
+                ..default()
+            },
+            UndoButton,
+            RelativeCursorPosition::default(),
+            Tooltip {
+                text: "Take back your last card this turn (Backspace)".to_string(),
+                threshold: 1.5,
+            },
+        ))
+        .with_children(|button| {
+            button.spawn((TextBundle::from_section(
+                "Undo",
+                TextStyle {
+                    font_size: 14.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),));
+        })
+        .set_parent(container_id);
     world
         .spawn((AtlasImageBundle {
             style: Style {
@@ -203,3 +239,19 @@ pub fn update_water_ui(
         .expect("Found more than one water UI text");
     text.sections[0].value = format!("{}/{}", water.current, water.maxium);
 }
+
+pub fn update_undo_button(
+    undo_stack: Res<UndoStack>,
+    mut button: Query<&mut BorderColor, With<UndoButton>>,
+) {
+    if !undo_stack.is_changed() {
+        return;
+    }
+    for mut border in button.iter_mut() {
+        border.0 = if undo_stack.0.is_empty() {
+            Color::GRAY
+        } else {
+            Color::WHITE
+        };
+    }
+}
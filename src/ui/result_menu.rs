@@ -51,9 +51,65 @@ pub fn handle_interactions(
     }
 }
 
+/// The stars, each line of the score and the total.
+fn spawn_score(parent: &mut WorldChildBuilder, score: &Score) {
+    let stars = score.stars();
+    let par = match &score.par {
+        Some(par) => format!("Par: {} turn(s), {} card(s)", par.turns, par.cards),
+        None => "No par for this level".to_string(),
+    };
+    parent.spawn((TextBundle::from_section(
+        format!("{} / 3 stars", stars),
+        TextStyle {
+            font_size: 48.0,
+            color: Color::GOLD,
+            ..default()
+        },
+    ),));
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|lines| {
+            for (label, points) in score.breakdown() {
+                lines.spawn((TextBundle::from_section(
+                    format!("{}  {:+}", label, points),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),));
+            }
+            lines.spawn((TextBundle::from_section(
+                format!("Score: {}", score.points()),
+                TextStyle {
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),));
+            lines.spawn((TextBundle::from_section(
+                par,
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::GRAY,
+                    ..default()
+                },
+            ),));
+        });
+}
+
 impl bevy::ecs::system::Command for SpawnMenuUI {
     fn apply(self, world: &mut World) {
-        let was_win = false;
+        let score = world.get_resource::<LastScore>().unwrap().0.clone();
+        let was_win = score.as_ref().is_some_and(|score| score.won);
         let game_mode = world.get_resource::<GameMode>().unwrap().clone();
         world
             .spawn((
@@ -81,6 +137,9 @@ impl bevy::ecs::system::Command for SpawnMenuUI {
                         ..default()
                     },
                 ),));
+                if let Some(score) = score.as_ref().filter(|score| score.won) {
+                    spawn_score(parent, score);
+                }
                 if !was_win && game_mode == GameMode::Puzzle {
                     parent
                         .spawn((
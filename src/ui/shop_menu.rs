@@ -0,0 +1,310 @@
+use bevy::ui::RelativeCursorPosition;
+
+use super::*;
+
+struct SpawnMenuUI;
+
+#[derive(Component)]
+pub struct ShopMenu;
+
+#[derive(Component, Debug)]
+pub enum ShopMenuOption {
+    Take(ContentID),
+    Remove(DeckCard),
+    Upgrade(DeckCard),
+    Skip,
+}
+
+#[derive(Event)]
+pub enum ShopMenuEvent {
+    TakePressed(ContentID),
+    RemovePressed(DeckCard),
+    UpgradePressed(DeckCard),
+    SkipPressed,
+}
+
+pub fn despawn(mut commands: Commands, menu_query: Query<Entity, With<ShopMenu>>) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn spawn(mut commands: Commands) {
+    commands.add(SpawnMenuUI);
+}
+
+pub fn handle_interactions(
+    mut events: EventWriter<ShopMenuEvent>,
+    interaction_query: Query<(&Interaction, &ShopMenuOption), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, option) in interaction_query.iter() {
+        match (*interaction, option) {
+            (Interaction::Pressed, ShopMenuOption::Take(content_id)) => {
+                events.send(ShopMenuEvent::TakePressed(content_id.clone()));
+            }
+            (Interaction::Pressed, ShopMenuOption::Remove(card)) => {
+                events.send(ShopMenuEvent::RemovePressed(card.clone()));
+            }
+            (Interaction::Pressed, ShopMenuOption::Upgrade(card)) => {
+                events.send(ShopMenuEvent::UpgradePressed(card.clone()));
+            }
+            (Interaction::Pressed, ShopMenuOption::Skip) => {
+                events.send(ShopMenuEvent::SkipPressed);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn section_title(parent: &mut WorldChildBuilder, text: &str) {
+    parent.spawn((TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: 32.0,
+            color: Color::GRAY,
+            ..default()
+        },
+    ),));
+}
+
+fn cards_row(parent: &mut WorldChildBuilder, children: impl FnOnce(&mut WorldChildBuilder)) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(8.0),
+                row_gap: Val::Px(8.0),
+                max_width: Val::Vw(80.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(children);
+}
+
+/// A small button for a card already in the deck. Without an option it is
+/// drawn dimmed and does nothing.
+fn deck_card_button(
+    parent: &mut WorldChildBuilder,
+    label: String,
+    action: Option<(String, ShopMenuOption)>,
+) {
+    let mut button = parent.spawn((
+        ButtonBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                min_height: Val::Px(40.0),
+                padding: UiRect::horizontal(Val::Px(12.0)),
+                ..default()
+            },
+            background_color: if action.is_some() {
+                Color::DARK_GRAY.into()
+            } else {
+                Color::rgb(0.15, 0.15, 0.15).into()
+            },
+            ..default()
+        },
+        RelativeCursorPosition::default(),
+    ));
+    if let Some((tooltip, option)) = action {
+        button.insert((
+            Tooltip {
+                text: tooltip,
+                threshold: 0.5,
+            },
+            option,
+        ));
+    }
+    button.with_children(|button| {
+        button.spawn((TextBundle::from_section(
+            label,
+            TextStyle {
+                font_size: 24.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),));
+    });
+}
+
+impl bevy::ecs::system::Command for SpawnMenuUI {
+    fn apply(self, world: &mut World) {
+        let card_infos = world.get_resource::<CardInfoMap>().unwrap().0.clone();
+        let offer = world.get_resource::<ShopOffer>().unwrap().0.clone();
+        let deck_list = world.get_resource::<DeckList>().unwrap().0.clone();
+        let can_remove = deck_list.len() > MIN_DECK_SIZE;
+        // Each distinct card in the deck once, in the order it was first added
+        let mut deck_counts: Vec<(DeckCard, CardInfo, usize)> = Vec::new();
+        for card in deck_list.iter() {
+            match deck_counts.iter_mut().find(|(entry, _, _)| entry == card) {
+                Some((_, _, count)) => *count += 1,
+                None => {
+                    if let Some(card_info) = card_infos.get(&card.content_id) {
+                        deck_counts.push((card.clone(), card_info.clone(), 1));
+                    }
+                }
+            }
+        }
+        let upgradable: Vec<&(DeckCard, CardInfo, usize)> = deck_counts
+            .iter()
+            .filter(|(card, card_info, _)| card.upgrade < card_info.max_upgrade())
+            .collect();
+        world
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::SpaceEvenly,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::BLACK.into(),
+                    ..default()
+                },
+                ShopMenu,
+            ))
+            .with_children(|parent| {
+                parent.spawn((TextBundle::from_section(
+                    "Shop",
+                    TextStyle {
+                        font_size: 80.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),));
+                section_title(parent, "Take a card");
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(16.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for content_id in offer.iter() {
+                            let Some(card_info) = card_infos.get(content_id) else {
+                                continue;
+                            };
+                            row.spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Column,
+                                        align_items: AlignItems::Center,
+                                        justify_content: JustifyContent::Center,
+                                        min_height: Val::Px(128.0),
+                                        width: Val::Vw(22.0),
+                                        padding: UiRect::all(Val::Px(8.0)),
+                                        ..default()
+                                    },
+                                    background_color: Color::TEAL.into(),
+                                    ..default()
+                                },
+                                ShopMenuOption::Take(content_id.clone()),
+                            ))
+                            .with_children(|button| {
+                                button.spawn((TextBundle::from_section(
+                                    card_info.name.clone(),
+                                    TextStyle {
+                                        font_size: 32.0,
+                                        color: Color::WHITE,
+                                        ..default()
+                                    },
+                                ),));
+                                button.spawn((TextBundle::from_section(
+                                    card_info.full_description(),
+                                    TextStyle {
+                                        font_size: 20.0,
+                                        color: Color::WHITE,
+                                        ..default()
+                                    },
+                                ),));
+                                button.spawn((TextBundle::from_section(
+                                    format!(
+                                        "Energy {}  Water {}",
+                                        card_info.resource_cost.energy,
+                                        card_info.resource_cost.water
+                                    ),
+                                    TextStyle {
+                                        font_size: 20.0,
+                                        color: Color::WHITE,
+                                        ..default()
+                                    },
+                                ),));
+                            });
+                        }
+                    });
+                section_title(
+                    parent,
+                    if can_remove {
+                        "Or remove one from your deck"
+                    } else {
+                        "Your deck is too small to remove a card"
+                    },
+                );
+                cards_row(parent, |row| {
+                    for (card, card_info, count) in deck_counts.iter() {
+                        let name = card_info.upgraded(card.upgrade).name;
+                        let action = can_remove.then(|| {
+                            (
+                                format!("Remove one {} from your deck", name),
+                                ShopMenuOption::Remove(card.clone()),
+                            )
+                        });
+                        deck_card_button(row, format!("{} x{}", name, count), action);
+                    }
+                });
+                section_title(
+                    parent,
+                    if upgradable.is_empty() {
+                        "None of your cards can be upgraded"
+                    } else {
+                        "Or upgrade one"
+                    },
+                );
+                cards_row(parent, |row| {
+                    for (card, card_info, _) in upgradable.iter() {
+                        let current = card_info.upgraded(card.upgrade);
+                        let next = card_info.upgraded(card.upgrade + 1);
+                        let action = Some((
+                            format!("{}: {}", next.name, next.full_description()),
+                            ShopMenuOption::Upgrade(card.clone()),
+                        ));
+                        deck_card_button(row, format!("{} -> {}", current.name, next.name), action);
+                    }
+                });
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                min_height: Val::Px(64.0),
+                                min_width: Val::Vw(30.0),
+                                ..default()
+                            },
+                            background_color: Color::MAROON.into(),
+                            ..default()
+                        },
+                        ShopMenuOption::Skip,
+                    ))
+                    .with_children(|button| {
+                        button.spawn((TextBundle::from_section(
+                            "Skip",
+                            TextStyle {
+                                font_size: 40.0,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),));
+                    });
+            });
+    }
+}
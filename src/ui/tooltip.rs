@@ -108,15 +108,15 @@ pub fn trigger_tooltip(
 pub fn add_tooltips_to_cards(
     mut commands: Commands,
     card_info: Res<CardInfoMap>,
-    content_ids: Query<&ContentID>,
+    content_ids: Query<(&ContentID, Option<&Upgrade>)>,
     card_uis: Query<(Entity, &CardInstance), (With<Interaction>, Without<Tooltip>)>,
 ) {
     for (card_ui_id, card_instance) in card_uis.iter() {
         if let Some(card_instance_id) = card_instance.0 {
-            if let Ok(content_id) = content_ids.get(card_instance_id) {
-                if let Some(card_info) = card_info.0.get(content_id) {
+            if let Ok((content_id, upgrade)) = content_ids.get(card_instance_id) {
+                if let Some(card_info) = card_info.effective(content_id, upgrade) {
                     commands.entity(card_ui_id).insert(Tooltip {
-                        text: card_info.description.clone(),
+                        text: card_info.full_description(),
                         threshold: 1.0,
                     });
                 }